@@ -0,0 +1,111 @@
+//! 対局を行うエージェント
+//!
+//! エージェントは自分から見える情報(自分の手牌、各席の捨て牌と鳴いた面子、持ち点など)を
+//! 受け取り、選べる行動の中から1つを返す
+
+use crate::groups::OpenSet;
+use crate::round::{Action, Phase, Player, RiverTile, Round, Seat};
+use crate::shanten;
+use crate::tiles::{Tile, Wind};
+use crate::wall::WallTile;
+
+/// 席から見える卓の状況
+#[derive(Debug, Clone)]
+pub struct Observation<'a> {
+    pub seat: Seat,
+    /// 自分の手牌、捨て牌、立直の状況
+    pub player: &'a Player,
+    /// ツモった牌 (鳴いた後や他家の手番では`None`)
+    pub drawn: Option<&'a WallTile>,
+    pub phase: &'a Phase,
+    /// 各席の捨て牌
+    pub rivers: Vec<&'a [RiverTile]>,
+    /// 各席の鳴いた面子
+    pub melds: Vec<&'a [OpenSet]>,
+    /// 各席が立直しているか
+    pub riichi: Vec<bool>,
+    /// 各席の持ち点
+    pub scores: &'a [i32],
+    pub dora_indicators: Vec<Tile>,
+    pub prevalent_wind: Wind,
+    pub seat_wind: Wind,
+    /// 親の席
+    pub dealer: Seat,
+    pub honba: u32,
+    pub riichi_sticks: u32,
+    /// 山の残り枚数
+    pub remaining: usize,
+    /// 選べる行動
+    pub legal_actions: Vec<Action>,
+}
+
+impl<'a> Observation<'a> {
+    pub fn new(round: &'a Round, seat: Seat) -> Self {
+        let players = round.players();
+        Observation {
+            seat,
+            player: round.player(seat),
+            drawn: if round.phase() == &Phase::Turn(seat) { round.drawn() } else { None },
+            phase: round.phase(),
+            rivers: players.iter().map(|player| player.river.as_slice()).collect(),
+            melds: players.iter().map(|player| player.melds.as_slice()).collect(),
+            riichi: players.iter().map(|player| player.riichi).collect(),
+            scores: round.scores(),
+            dora_indicators: round.wall().dora_indicators(),
+            prevalent_wind: round.prevalent_wind().clone(),
+            seat_wind: round.seat_wind(seat),
+            dealer: round.dealer(),
+            honba: round.honba(),
+            riichi_sticks: round.riichi_sticks(),
+            remaining: round.wall().remaining(),
+            legal_actions: round.legal_actions(seat),
+        }
+    }
+}
+
+/// エージェント
+pub trait Agent {
+    /// 表示名
+    fn name(&self) -> String;
+
+    /// 行動を選ぶ (`observation.legal_actions`のいずれかを返す)
+    fn act(&mut self, observation: &Observation) -> Action;
+}
+
+/// 基準となるエージェント
+///
+/// 和了できれば和了し、鳴かずに向聴数が最も小さく有効牌が最も多くなる牌を打牌する。
+/// 打牌で聴牌する場合は立直し、北抜きできれば抜く
+#[derive(Debug, Clone, Default)]
+pub struct BaselineAgent;
+
+impl Agent for BaselineAgent {
+    fn name(&self) -> String {
+        "baseline".to_string()
+    }
+
+    fn act(&mut self, observation: &Observation) -> Action {
+        let legal = &observation.legal_actions;
+        let preferred = [Action::Tsumo, Action::Ron, Action::Nukidora, Action::Pass];
+        if let Some(action) = preferred.iter().find(|action| legal.contains(action)) {
+            return action.clone();
+        }
+
+        let player = observation.player;
+        for discard in shanten::discards(&player.tiles(), &player.melds) {
+            let riichi = legal.iter().find(|action| matches!(action, Action::Riichi(tile) if tile.tile == discard.tile));
+            if let Some(riichi) = riichi {
+                return riichi.clone();
+            }
+            // 赤五は残す
+            let mut candidates: Vec<&Action> = legal.iter()
+                .filter(|action| matches!(action, Action::Discard(tile) if tile.tile == discard.tile))
+                .collect();
+            candidates.sort_by_key(|action| matches!(action, Action::Discard(tile) if tile.red));
+            if let Some(action) = candidates.first() {
+                return (*action).clone();
+            }
+        }
+        legal[0].clone()
+    }
+}
@@ -0,0 +1,320 @@
+//! 鳴きと和了の可否
+//!
+//! 席ごとの手牌と晒した面子、捨て牌から、打牌に対する応答(チー、ポン、大明槓、ロン)と
+//! ツモ後の行動(ツモ和了、暗槓、加槓、立直)を列挙する
+
+use crate::evaluate::{Evaluated, Evaluator};
+use crate::groups::{Hand, OpenSet, Source, Tiles};
+use crate::parse::ParsedHand;
+use crate::round::{Player, Seat};
+use crate::rules::Rules;
+use crate::shanten::{acceptance, shanten};
+use crate::tiles::{Tile, Wind};
+use crate::wall::WallTile;
+use crate::yaku::situation::SituationYaku;
+
+/// 行動
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// 打牌
+    Discard(WallTile),
+    /// 立直を宣言して打牌する
+    Riichi(WallTile),
+    /// ツモ和了
+    Tsumo,
+    /// 暗槓
+    ConcealedKong(Tile),
+    /// 加槓
+    AddedKong(Tile),
+    /// ロン和了 (槍槓を含む)
+    Ron,
+    /// チー (手牌から出す2枚)
+    Chow(WallTile, WallTile),
+    /// ポン (手牌から出す2枚)
+    Pung(WallTile, WallTile),
+    /// 大明槓
+    Kong,
+    /// 北抜き (三人麻雀)
+    Nukidora,
+    /// 九種九牌で流局にする
+    NineTerminals,
+    /// 見送り
+    Pass,
+}
+
+impl Action {
+    /// 打牌に対する応答の優先順位 (ロン > ポン・大明槓 > チー > 見送り)
+    pub fn priority(&self) -> u8 {
+        match self {
+            Action::Ron => 3,
+            Action::Pung(..) | Action::Kong => 2,
+            Action::Chow(..) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// 卓の状況による制限
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Allowed {
+    /// ポン・チーできるか (河底牌は鳴けない)
+    pub call: bool,
+    /// 槓できるか (嶺上牌が残っていて海底でない)
+    pub kong: bool,
+    /// 立直できるか (山が残っていて持ち点が足りる)
+    pub riichi: bool,
+    /// 九種九牌を宣言できるか (鳴きのない1巡目の最初のツモ)
+    pub nine_terminals: bool,
+    /// 北抜きできるか (北抜きありのルールで海底でない)
+    pub nukidora: bool,
+}
+
+/// 和了の判定に使う状況
+#[derive(Debug)]
+pub struct WinContext {
+    pub prevalent_wind: Wind,
+    pub seat_wind: Wind,
+    /// ドラ (表示牌ではなくドラそのもの)
+    pub dora: Vec<Tile>,
+    /// 裏ドラ
+    pub ura_dora: Vec<Tile>,
+    pub rules: Rules,
+    /// 状況役
+    pub situation: Vec<SituationYaku>,
+    /// 抜いた北の枚数
+    pub nukidora: u32,
+}
+
+impl WinContext {
+    /// 和了の手牌と点数 (和了形でないか役がない場合は`None`)
+    ///
+    /// ロンの場合は`winning`を手牌に加え、ツモの場合は手牌に含まれているものとする
+    pub fn evaluate(&self, player: &Player, winning: &WallTile, draw: bool) -> Option<(Hand, Evaluated)> {
+        let mut tiles = player.tiles();
+        let mut red_fives = player.red_fives();
+        if !draw {
+            tiles.push(winning.tile.clone());
+            if winning.red {
+                red_fives.push(winning.tile.clone());
+                red_fives.sort();
+            }
+        }
+        if shanten(&tiles, &player.melds) != -1 {
+            return None;
+        }
+        tiles.sort();
+        let hand = Hand { tiles, open_sets: player.melds.clone(), winning: winning.tile.clone(), draw: Some(draw), red_fives, flowers: 0 };
+
+        let mut evaluator = Evaluator::new(Some(self.prevalent_wind.clone().tile()), Some(self.seat_wind.clone().tile()),
+                                           self.dora.clone(), self.ura_dora.clone());
+        evaluator.set_rules(self.rules.clone());
+        evaluator.set_nukidora(self.nukidora);
+        let evaluated = evaluator.evaluate(&ParsedHand::new(&hand), draw, &self.situation)?;
+        if evaluated.score().han().0 == 0 {
+            return None;
+        }
+        Some((hand, evaluated))
+    }
+}
+
+/// 待ち (加えると和了形になる牌、枚数は問わない)
+pub fn waits(player: &Player) -> Vec<Tile> {
+    let mut tiles = player.tiles();
+    Tile::all().into_iter().filter(|tile| {
+        tiles.push(tile.clone());
+        let complete = shanten(&tiles, &player.melds) == -1;
+        tiles.pop();
+        complete
+    }).collect()
+}
+
+/// フリテンか
+///
+/// 待ちのいずれかを自分で捨てている(鳴かれた牌を含む)か、見逃した場合
+pub fn is_furiten(player: &Player) -> bool {
+    player.missed_win || waits(player).iter().any(|tile| player.river.iter().any(|river| &river.tile.tile == tile))
+}
+
+/// ロンできるか (和了形で役があり、フリテンでない)
+pub fn can_ron(player: &Player, tile: &WallTile, context: &WinContext) -> bool {
+    context.evaluate(player, tile, false).is_some() && !is_furiten(player)
+}
+
+/// ポンの組み合わせ (赤五の有無で区別する)
+pub fn pungs(player: &Player, tile: &Tile) -> Vec<Action> {
+    let same = player.distinct(tile);
+    let mut actions = Vec::new();
+    for (i, first) in same.iter().enumerate() {
+        for second in &same[i..] {
+            if first == second && player.concealed.iter().filter(|each| *each == first).count() < 2 {
+                continue;
+            }
+            if vec![first.tile.clone(), second.tile.clone(), tile.clone()].is_flat() {
+                actions.push(Action::Pung(first.clone(), second.clone()));
+            }
+        }
+    }
+    actions
+}
+
+/// チーの組み合わせ (赤五の有無で区別する)
+pub fn chows(player: &Player, tile: &Tile) -> Vec<Action> {
+    let mut candidates: Vec<WallTile> = player.concealed.iter()
+        .filter(|each| each.tile.is_suits() && &each.tile != tile)
+        .cloned().collect();
+    candidates.sort();
+    candidates.dedup();
+    let mut actions = Vec::new();
+    for (i, first) in candidates.iter().enumerate() {
+        for second in candidates[i + 1..].iter().filter(|second| second.tile != first.tile) {
+            let mut tiles = vec![first.tile.clone(), second.tile.clone(), tile.clone()];
+            tiles.sort();
+            if tiles.is_sequential() {
+                actions.push(Action::Chow(first.clone(), second.clone()));
+            }
+        }
+    }
+    actions
+}
+
+/// 打牌に対する応答 (応答できない場合は空、できる場合は見送りを含む)
+///
+/// `from`は打牌した相手の位置で、チーは上家からのみ(三人麻雀ではチーなし)できる。`context`は
+/// ロンした場合の状況
+pub fn responses(player: &Player, discard: &WallTile, from: Source, context: &WinContext, allowed: Allowed) -> Vec<Action> {
+    let mut actions = Vec::new();
+    if can_ron(player, discard, context) {
+        actions.push(Action::Ron);
+    }
+    if allowed.call && !player.riichi {
+        actions.extend(pungs(player, &discard.tile));
+        if allowed.kong && player.count(&discard.tile) >= 3 {
+            actions.push(Action::Kong);
+        }
+        if from == Source::Left && !context.rules.sanma {
+            actions.extend(chows(player, &discard.tile));
+        }
+    }
+    if !actions.is_empty() {
+        actions.push(Action::Pass);
+    }
+    actions
+}
+
+/// 加槓に対する応答 (槍槓)
+pub fn robbing_responses(player: &Player, tile: &WallTile, context: &WinContext) -> Vec<Action> {
+    if can_ron(player, tile, context) { vec![Action::Ron, Action::Pass] } else { Vec::new() }
+}
+
+/// 九種九牌か (么九牌が9種類以上)
+pub fn is_nine_terminals(player: &Player) -> bool {
+    let mut yaotyu: Vec<Tile> = player.tiles().into_iter().filter(|tile| tile.is_yaotyu()).collect();
+    yaotyu.sort();
+    yaotyu.dedup();
+    yaotyu.len() >= 9
+}
+
+/// 立直の宣言牌の候補 (打牌すると聴牌になる牌)
+pub fn riichi_candidates(player: &Player) -> Vec<WallTile> {
+    if player.riichi || !player.is_closed() {
+        return Vec::new();
+    }
+    let mut candidates = player.concealed.clone();
+    candidates.sort();
+    candidates.dedup();
+    candidates.retain(|tile| {
+        let mut tiles = player.tiles();
+        tiles.remove(tiles.iter().position(|each| each == &tile.tile).unwrap());
+        shanten(&tiles, &player.melds) == 0
+    });
+    candidates
+}
+
+/// 暗槓できる牌 (立直後は待ちが変わらないツモ牌のみ)
+pub fn concealed_kongs(player: &Player, drawn: &WallTile) -> Vec<Tile> {
+    Tile::all().into_iter()
+        .filter(|tile| player.count(tile) == 4)
+        .filter(|tile| !player.riichi || (tile == &drawn.tile && keeps_waits(player, tile)))
+        .collect()
+}
+
+/// 加槓できる牌
+pub fn added_kongs(player: &Player) -> Vec<Tile> {
+    player.melds.iter().filter_map(|meld| match meld {
+        OpenSet::Pung(tiles, _) if player.count(&tiles[0]) > 0 => Some(tiles[0].clone()),
+        _ => None,
+    }).collect()
+}
+
+/// 立直後の暗槓で待ちが変わらないか
+fn keeps_waits(player: &Player, tile: &Tile) -> bool {
+    let mut before = player.tiles();
+    before.remove(before.iter().position(|each| each == tile).unwrap());
+    let after: Vec<Tile> = before.iter().filter(|each| *each != tile).cloned().collect();
+    let mut melds = player.melds.clone();
+    melds.push(OpenSet::ConcealedKong(vec![tile.clone(); 4]));
+    let waits = |tiles: &[Tile], melds: &[OpenSet]| -> Vec<Tile> {
+        acceptance(tiles, melds).into_iter().map(|(tile, _)| tile).collect()
+    };
+    waits(&before, &player.melds) == waits(&after, &melds)
+}
+
+/// ツモ後または鳴いた後の行動
+///
+/// `drawn`はツモった牌で、鳴いた後は`None`(打牌のみできる)。立直後はツモ切りと、ツモった北の
+/// 北抜きのみできる
+pub fn self_actions(player: &Player, drawn: Option<&WallTile>, context: &WinContext, allowed: Allowed) -> Vec<Action> {
+    let mut actions = Vec::new();
+    if let Some(drawn) = drawn {
+        if context.evaluate(player, drawn, true).is_some() {
+            actions.push(Action::Tsumo);
+        }
+        if allowed.kong {
+            actions.extend(concealed_kongs(player, drawn).into_iter().map(Action::ConcealedKong));
+            if !player.riichi {
+                actions.extend(added_kongs(player).into_iter().map(Action::AddedKong));
+            }
+        }
+    }
+
+    let mut discards = player.concealed.clone();
+    discards.sort();
+    discards.dedup();
+    if player.riichi {
+        discards.retain(|tile| Some(tile) == drawn);
+    }
+    actions.extend(discards.into_iter().map(Action::Discard));
+    if allowed.riichi && drawn.is_some() {
+        actions.extend(riichi_candidates(player).into_iter().map(Action::Riichi));
+    }
+    let north = Wind::North.tile();
+    if allowed.nukidora && drawn.is_some_and(|drawn| !player.riichi || drawn.tile == north) && player.count(&north) > 0 {
+        actions.push(Action::Nukidora);
+    }
+    if allowed.nine_terminals && drawn.is_some() && is_nine_terminals(player) {
+        actions.push(Action::NineTerminals);
+    }
+    actions
+}
+
+/// ロンを選んだ席 (打牌した席`from`から近い順)
+pub fn ron_seats(responses: &[Option<Action>], from: Seat) -> Vec<Seat> {
+    let players = responses.len();
+    (1..players).map(|i| (from + i) % players).filter(|seat| responses[*seat] == Some(Action::Ron)).collect()
+}
+
+/// 応答を優先順位で解決する
+///
+/// 同じ優先順位の場合は打牌した席`from`から近い席を優先する。全員が見送った場合は`None`
+pub fn resolve(responses: &[Option<Action>], from: Seat) -> Option<(Seat, Action)> {
+    let players = responses.len();
+    let mut best: Option<(Seat, Action)> = None;
+    for seat in (1..players).map(|i| (from + i) % players) {
+        if let Some(action) = responses[seat].as_ref().filter(|action| action.priority() > 0) {
+            if best.as_ref().is_none_or(|(_, best)| action.priority() > best.priority()) {
+                best = Some((seat, action.clone()));
+            }
+        }
+    }
+    best
+}
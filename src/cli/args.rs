@@ -0,0 +1,327 @@
+use mahjong::groups::TilesNewType;
+use mahjong::rules::Rules;
+use mahjong::tiles::{Honour, Tile, Wind};
+use std::str::FromStr;
+
+/// サブコマンド
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// 和了形の点数計算
+    Score,
+    /// 聴牌形の待ち
+    Waits,
+    /// 向聴数
+    Shanten,
+    /// 打牌候補
+    Discard,
+    /// 対話モード
+    Repl,
+    /// ファイルの一括計算
+    Batch,
+    /// 点数から翻数と符を逆引きする
+    Lookup,
+    /// 点数表
+    Table,
+    /// 基準エージェント同士の対局の成績
+    Simulate,
+    /// 基準エージェント同士の1対局の牌譜
+    Record,
+    /// 牌譜の再生と検証
+    Replay,
+    /// 使い方
+    Help,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Result<Self, failure::Error> {
+        match name {
+            "score" => Ok(Command::Score),
+            "waits" => Ok(Command::Waits),
+            "shanten" => Ok(Command::Shanten),
+            "discard" => Ok(Command::Discard),
+            "repl" => Ok(Command::Repl),
+            "batch" => Ok(Command::Batch),
+            "lookup" => Ok(Command::Lookup),
+            "table" => Ok(Command::Table),
+            "simulate" => Ok(Command::Simulate),
+            "record" => Ok(Command::Record),
+            "replay" => Ok(Command::Replay),
+            "help" | "-h" | "--help" => Ok(Command::Help),
+            _ => Err(format_err!("不明なコマンドです: {}", name)),
+        }
+    }
+}
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    /// 点数表のみ
+    Csv,
+    /// 点数表のみ
+    Markdown,
+}
+
+/// コマンドラインの指定
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub command: Command,
+    /// 手牌の譜面
+    pub hand: String,
+    /// 場風
+    pub prevalent_wind: Option<Tile>,
+    /// 自風
+    pub seat_wind: Option<Tile>,
+    /// ドラ表示牌
+    pub dora_indicators: Vec<Tile>,
+    /// 裏ドラ表示牌
+    pub ura_dora_indicators: Vec<Tile>,
+    /// 立直
+    pub riichi: bool,
+    /// ダブル立直
+    pub double_riichi: bool,
+    /// 一発
+    pub ippatsu: bool,
+    /// ツモ和了か否か (指定がない場合は譜面の指定、それもなければロン)
+    pub draw: Option<bool>,
+    /// 海底摸月
+    pub last_tile: bool,
+    /// 河底撈魚
+    pub last_discard: bool,
+    /// 嶺上開花
+    pub dead_wall_draw: bool,
+    /// 槍槓
+    pub robbing_a_quad: bool,
+    /// 和了者が親か否か (自風の指定がない場合)
+    pub dealer: bool,
+    /// 本場
+    pub honba: u32,
+    /// 供託のリーチ棒の本数
+    pub riichi_sticks: u32,
+    /// 抜いた北の枚数
+    pub nukidora: u32,
+    /// 乱数のシード (simulate, record)
+    pub seed: u64,
+    /// 採用するルール
+    pub rules: Rules,
+    /// 出力形式
+    pub format: Format,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            command: Command::Help,
+            hand: String::new(),
+            prevalent_wind: None,
+            seat_wind: None,
+            dora_indicators: Vec::new(),
+            ura_dora_indicators: Vec::new(),
+            riichi: false,
+            double_riichi: false,
+            ippatsu: false,
+            draw: None,
+            last_tile: false,
+            last_discard: false,
+            dead_wall_draw: false,
+            robbing_a_quad: false,
+            dealer: false,
+            honba: 0,
+            riichi_sticks: 0,
+            nukidora: 0,
+            seed: 0,
+            rules: Rules::standard(),
+            format: Format::Text,
+        }
+    }
+}
+
+impl Options {
+    /// 引数を読み取る (`mahjong <command> <hand> [options]`)
+    ///
+    /// 手牌以外の引数は手牌の後に空白区切りで連結する (`7s tsumo`のような当たり牌の指定のため)
+    pub fn parse(args: &[String]) -> Result<Self, failure::Error> {
+        let mut options = Options::default();
+        let mut iter = args.iter();
+        options.command = match iter.next() {
+            Some(name) => Command::from_name(name)?,
+            None => return Ok(options),
+        };
+        let rest: Vec<String> = iter.cloned().collect();
+        options.hand = options.apply(&rest)?;
+        if options.hand.is_empty() && ![Command::Help, Command::Repl, Command::Table, Command::Record].contains(&options.command) {
+            return Err(format_err!("手牌を指定してください"));
+        }
+        Ok(options)
+    }
+
+    /// オプションを反映し、オプション以外の引数を空白区切りで連結して返す
+    ///
+    /// 真偽値のオプションは`--no-riichi`のように`--no-`を付けると取り消せる
+    pub fn apply(&mut self, args: &[String]) -> Result<String, failure::Error> {
+        let mut hand = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with("--") {
+                hand.push(arg.as_str());
+                continue;
+            }
+            // `--flag=value`と`--flag value`のどちらも受け付ける
+            let (flag, inline) = match arg.find('=') {
+                Some(i) => (&arg[..i], Some(arg[i + 1..].to_string())),
+                None => (arg.as_str(), None),
+            };
+            // 真偽値のオプションは`--riichi=false`のような値を受け付けない
+            let no_value = || match &inline {
+                Some(_) => Err(format_err!("{}は値を取りません: {}", flag, arg)),
+                None => Ok(()),
+            };
+            if let Some(name) = flag.strip_prefix("--no-") {
+                no_value()?;
+                self.set_flag(name, false)?;
+                continue;
+            }
+            let mut value = || -> Result<String, failure::Error> {
+                match &inline {
+                    Some(value) => Ok(value.clone()),
+                    None => iter.next().cloned().ok_or_else(|| format_err!("{}に値がありません", flag)),
+                }
+            };
+            match flag {
+                "--round" => self.prevalent_wind = Some(parse_wind(&value()?)?),
+                "--seat" => self.seat_wind = Some(parse_wind(&value()?)?),
+                "--dora" => self.dora_indicators.append(&mut parse_tiles(&value()?)?),
+                "--ura" | "--ura-dora" => self.ura_dora_indicators.append(&mut parse_tiles(&value()?)?),
+                "--tsumo" => { no_value()?; self.set_draw(true)? }
+                "--ron" => { no_value()?; self.set_draw(false)? }
+                "--honba" => self.honba = parse_number(flag, &value()?)?,
+                "--sticks" | "--riichi-sticks" => self.riichi_sticks = parse_number(flag, &value()?)?,
+                "--rules" => self.rules = Rules::preset(&value()?)?,
+                "--nukidora" | "--kita" => self.nukidora = parse_number(flag, &value()?)?,
+                "--seed" => self.seed = parse_number(flag, &value()?)?,
+                "--format" => self.format = match value()?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    "markdown" | "md" => Format::Markdown,
+                    format => return Err(format_err!("出力形式が不正です: {} (text, json, csv, markdownのいずれか)", format)),
+                },
+                "--help" => { no_value()?; self.command = Command::Help }
+                _ => { no_value()?; self.set_flag(&flag[2..], true)? }
+            }
+        }
+        Ok(hand.join(" "))
+    }
+
+    /// 真偽値のオプションを設定する
+    fn set_flag(&mut self, name: &str, value: bool) -> Result<(), failure::Error> {
+        match name {
+            "riichi" => self.riichi = value,
+            "double-riichi" => self.double_riichi = value,
+            "ippatsu" => self.ippatsu = value,
+            "haitei" => self.last_tile = value,
+            "houtei" => self.last_discard = value,
+            "rinshan" => self.dead_wall_draw = value,
+            "chankan" => self.robbing_a_quad = value,
+            "dealer" => self.dealer = value,
+            "kiriage" => self.rules.kiriage = value,
+            "flowers" => self.rules.flowers = value,
+            "tsumo-loss" => self.rules.tsumo_loss = value,
+            _ => return Err(format_err!("不明なオプションです: --{}", name)),
+        }
+        Ok(())
+    }
+
+    /// 和了ごとの状況(立直、ツモ/ロン、一発など)を取り消す
+    ///
+    /// 場風、自風、ドラ、本場などの卓の状況は残す
+    pub fn reset_situation(&mut self) {
+        let default = Options::default();
+        self.ura_dora_indicators = default.ura_dora_indicators;
+        self.riichi = default.riichi;
+        self.double_riichi = default.double_riichi;
+        self.ippatsu = default.ippatsu;
+        self.draw = default.draw;
+        self.last_tile = default.last_tile;
+        self.last_discard = default.last_discard;
+        self.dead_wall_draw = default.dead_wall_draw;
+        self.robbing_a_quad = default.robbing_a_quad;
+        self.nukidora = default.nukidora;
+    }
+
+    fn set_draw(&mut self, draw: bool) -> Result<(), failure::Error> {
+        if self.draw == Some(!draw) {
+            return Err(format_err!("--tsumoと--ronは同時に指定できません"));
+        }
+        self.draw = Some(draw);
+        Ok(())
+    }
+}
+
+/// 使い方
+pub fn usage() -> String {
+    format!("\
+usage: mahjong <command> <hand> [options]
+
+commands:
+  score     和了形の点数を計算する (例: 123m456p789s11z[5^55p]+1z)
+  waits     聴牌形の待ちと点数を表示する (13枚)
+  shanten   向聴数を表示する
+  discard   打牌候補と有効牌を表示する (14枚)
+  repl      対話モードで続けて点数を計算する
+  batch     ファイルの手牌を一括で計算し、期待値と比較する (<hand>の代わりにファイル名、-は標準入力)
+  lookup    点数から翻数と符を逆引きする (<hand>の代わりに7700、1300-2600、2600allなど)
+  table     翻数×符の点数表を表示する
+  simulate  基準エージェント同士で対局して成績を表示する (<hand>の代わりに対局数)
+  record    基準エージェント同士で1対局して牌譜を表示する (<hand>は不要)
+  replay    牌譜を再生して各局の結果と点数を検証する (<hand>の代わりにファイル名、-は標準入力)
+
+options:
+  --round <wind>     場風 (E, S, W, N / 東南西北 / 1z-4z)
+  --seat <wind>      自風
+  --dora <tiles>     ドラ表示牌 (例: 3m7z)
+  --ura <tiles>      裏ドラ表示牌 (立直時のみ)
+  --riichi, --double-riichi, --ippatsu
+  --tsumo, --ron
+  --haitei, --houtei, --rinshan, --chankan
+  --honba <n>        本場
+  --sticks <n>       供託のリーチ棒の本数
+  --rules <preset>   ルール ({})
+  --nukidora <n>     抜いた北の枚数 (--rules sanmaの場合)
+  --seed <n>         乱数のシード (simulate, record)
+  --kiriage          切り上げ満貫を採用する (--rulesの後に指定)
+  --flowers          花牌・季節牌(🀢-🀩)を1枚1翻のドラとして採用する (--rulesの後に指定)
+  --no-tsumo-loss    三人麻雀のツモ和了で北家の分を折半して支払う (--rulesの後に指定)
+  --dealer           自風を指定せずに親として計算する
+  --format <format>  出力形式 (text, json, tableはtext, csv, markdown)
+", Rules::preset_names().join(", "))
+}
+
+/// 風の指定を読み取る
+fn parse_wind(s: &str) -> Result<Tile, failure::Error> {
+    let wind = match s.to_ascii_lowercase().as_str() {
+        "e" | "east" => Some(Wind::East.tile()),
+        "s" | "south" => Some(Wind::South.tile()),
+        "w" | "west" => Some(Wind::West.tile()),
+        "n" | "north" => Some(Wind::North.tile()),
+        _ => match TilesNewType::from_str_with_red(s) {
+            Ok((TilesNewType(tiles), _)) if tiles.len() == 1 => tiles.into_iter().next(),
+            _ => None,
+        },
+    };
+    match wind {
+        Some(tile @ Tile::Honour(Honour::Wind(_))) => Ok(tile),
+        _ => Err(format_err!("風の指定が不正です: {}", s)),
+    }
+}
+
+/// 牌の並びを読み取る (赤五は通常の5とみなす)
+fn parse_tiles(s: &str) -> Result<Vec<Tile>, failure::Error> {
+    let (TilesNewType(tiles), _) = TilesNewType::from_str_with_red(s)?;
+    Ok(tiles)
+}
+
+fn parse_number<T: FromStr>(flag: &str, s: &str) -> Result<T, failure::Error> {
+    s.parse().map_err(|_| format_err!("{}の値が不正です: {}", flag, s))
+}
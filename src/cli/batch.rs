@@ -0,0 +1,145 @@
+//! 手牌ファイルの一括計算
+//!
+//! 1行に1つの手牌を書き、`|`の後に期待値を書く。`#`以降と空行は無視する
+//!
+//! ```text
+//! # 手牌とオプション | 期待値
+//! 234m234p234s88p567s+7s --riichi --tsumo --dora 7p | han=8 fu=20 points=16000 yaku=ready_hand,self_pick,all_simple,no_points_hand,three_colour_straight,dora
+//! [111z]234m234p567s88p+8p --round E --seat S | han=1 fu=30 points=1000
+//! ```
+//!
+//! 期待値は`han`、`fu`(切り上げた値)、`points`(和了者が受け取る点数)、`limit`(`mangan`など、なしは`-`)、
+//! `yaku`(識別子を`,`で区切る、順不同)のうち必要なものだけを書けばよい。コマンドラインのオプションは全ての行に適用する
+
+use crate::cli::args::Options;
+use crate::cli::commands;
+use mahjong::yaku;
+use std::io::{BufRead, BufReader, Read};
+
+/// 1行分の期待値
+#[derive(Debug, Default)]
+pub struct Expected {
+    pub han: Option<u32>,
+    pub fu: Option<u32>,
+    pub points: Option<u32>,
+    pub limit: Option<String>,
+    pub yaku: Option<Vec<String>>,
+}
+
+impl Expected {
+    pub fn parse(s: &str) -> Result<Self, failure::Error> {
+        let mut expected = Expected::default();
+        for item in s.split_whitespace() {
+            let (key, value) = match item.find('=') {
+                Some(i) => (&item[..i], &item[i + 1..]),
+                None => return Err(format_err!("期待値の指定が不正です: {}", item)),
+            };
+            let number = || value.parse::<u32>().map_err(|_| format_err!("期待値の数値が不正です: {}", item));
+            match key {
+                "han" => expected.han = Some(number()?),
+                "fu" => expected.fu = Some(number()?),
+                "points" => expected.points = Some(number()?),
+                "limit" => expected.limit = Some(value.to_string()),
+                "yaku" => expected.yaku = Some(value.split(',').filter(|id| !id.is_empty()).map(String::from).collect()),
+                _ => return Err(format_err!("期待値の項目が不正です: {}", key)),
+            }
+        }
+        Ok(expected)
+    }
+}
+
+/// 1行を計算して期待値との差分を返す (一致した場合は空)
+pub fn check(base: &Options, line: &str) -> Result<Vec<String>, failure::Error> {
+    let (input, expected) = match line.find('|') {
+        Some(i) => (&line[..i], Expected::parse(&line[i + 1..])?),
+        None => (line, Expected::default()),
+    };
+    let tokens: Vec<String> = input.split_whitespace().map(String::from).collect();
+    let mut options = base.clone();
+    if tokens.iter().any(|token| token == "--tsumo" || token == "--ron") {
+        options.draw = None;
+    }
+    options.hand = options.apply(&tokens)?;
+    let (evaluated, draw, points) = commands::best(&options)?;
+
+    let mut diffs = Vec::new();
+    let han = evaluated.score().han().0;
+    let fu = evaluated.fu().round_up().0;
+    let limit = evaluated.score().limit_id().unwrap_or("-");
+    if let Some(expected) = expected.han.filter(|expected| *expected != han) {
+        diffs.push(format!("han: expected {}, actual {}", expected, han));
+    }
+    if let Some(expected) = expected.fu.filter(|expected| *expected != fu) {
+        let breakdown: Vec<String> = commands::fu_breakdown(&options, &evaluated, draw)?.iter()
+            .map(|(name, fu)| format!("{} {}", name, fu.0))
+            .collect();
+        diffs.push(format!("fu: expected {}, actual {} ({} = {}; {})",
+                           expected, fu, breakdown.join(" + "), evaluated.fu().0, evaluated.node()));
+    }
+    if let Some(expected) = expected.points.filter(|expected| *expected != points) {
+        diffs.push(format!("points: expected {}, actual {}", expected, points));
+    }
+    if let Some(expected) = expected.limit.filter(|expected| expected != limit) {
+        diffs.push(format!("limit: expected {}, actual {}", expected, limit));
+    }
+    if let Some(expected) = expected.yaku {
+        let actual: Vec<String> = evaluated.yaku_list().iter().map(|name| yaku::id(name)).collect();
+        let mut diff: Vec<String> = expected.iter().filter(|id| !actual.contains(id)).map(|id| format!("-{}", id)).collect();
+        diff.extend(actual.iter().filter(|id| !expected.contains(id)).map(|id| format!("+{}", id)));
+        if !diff.is_empty() {
+            diffs.push(format!("yaku: {}", diff.join(" ")));
+        }
+    }
+    Ok(diffs)
+}
+
+/// ファイルを読み取って全ての行を計算し、不一致と集計を出力する
+///
+/// 不一致または計算できない行があった場合は1を返す
+pub fn run(options: &Options) -> i32 {
+    let reader: Box<dyn Read> = if options.hand == "-" {
+        Box::new(std::io::stdin())
+    } else {
+        match std::fs::File::open(&options.hand) {
+            Ok(file) => Box::new(file),
+            Err(error) => {
+                eprintln!("error: {}を開けません: {}", options.hand, error);
+                return 1;
+            }
+        }
+    };
+
+    let (mut total, mut passed, mut mismatched, mut errors) = (0, 0, 0, 0);
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                return 1;
+            }
+        };
+        let line = match line.find('#') {
+            Some(i) => &line[..i],
+            None => &line[..],
+        }.trim();
+        if line.is_empty() {
+            continue;
+        }
+        total += 1;
+        match check(options, line) {
+            Ok(diffs) if diffs.is_empty() => passed += 1,
+            Ok(diffs) => {
+                mismatched += 1;
+                println!("line {}: {}", i + 1, line);
+                diffs.iter().for_each(|diff| println!("  {}", diff));
+            }
+            Err(error) => {
+                errors += 1;
+                println!("line {}: {}", i + 1, line);
+                println!("  error: {}", error);
+            }
+        }
+    }
+    println!("{} hands: {} passed, {} mismatched, {} errors", total, passed, mismatched, errors);
+    if mismatched + errors > 0 { 1 } else { 0 }
+}
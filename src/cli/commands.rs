@@ -0,0 +1,421 @@
+use crate::cli::args::{Command, Format, Options};
+use mahjong::agent::{Agent, BaselineAgent};
+use mahjong::evaluate::{Evaluated, Evaluator};
+use mahjong::game::MatchConfig;
+use mahjong::groups::{Hand, OpenSet};
+use mahjong::output::{json_string, Report};
+use mahjong::parse::ParsedHand;
+use mahjong::payment::Stakes;
+use mahjong::record::{Event, Record};
+use mahjong::score::Fu;
+use mahjong::shanten;
+use mahjong::simulator::{self, Simulator};
+use mahjong::table::{self, Entry, ScoreTable};
+use mahjong::tiles::{Tile, Wind};
+use mahjong::yaku::situation::SituationYaku;
+use std::str::FromStr;
+
+/// コマンドを実行して出力を返す
+pub fn execute(options: &Options) -> Result<String, failure::Error> {
+    if (options.format == Format::Csv || options.format == Format::Markdown) && options.command != Command::Table {
+        return Err(format_err!("csvとmarkdownはtableコマンドでのみ指定できます"));
+    }
+    match options.command {
+        Command::Score => score(options),
+        Command::Waits => waits(options),
+        Command::Shanten => shanten(options),
+        Command::Discard => discard(options),
+        Command::Lookup => lookup(options),
+        Command::Table => table(options),
+        Command::Simulate => simulate(options),
+        Command::Record => record(options),
+        Command::Replay => replay(options),
+        Command::Help => Ok(crate::cli::args::usage()),
+        Command::Repl | Command::Batch => Err(format_err!("このコマンドは標準出力に直接書き出します")),
+    }
+}
+
+/// 全ての解釈を高い順に表で表示する (`*`が採用される解釈)
+pub fn interpretations(options: &Options) -> Result<String, failure::Error> {
+    let hand = Hand::from_str_with_rules(&options.hand, &options.rules)?;
+    let draw = draw(options, &hand)?;
+    let evaluator = evaluator(options);
+    let is_dealer = is_dealer(options);
+    let mut text = String::from("   翻  符    点数  役 | 面子\n");
+    for (i, evaluated) in evaluate_all(options, &evaluator, &hand, draw)?.iter().rev().enumerate() {
+        let yaku = if evaluated.yaku_list().is_empty() { "役なし".to_string() } else { evaluated.yaku_list().join(", ") };
+        text.push_str(&format!("{} {:>3} {:>3} {:>7}  {} | {}\n",
+                               if i == 0 { '*' } else { ' ' },
+                               evaluated.score().han().0,
+                               evaluated.fu().round_up().0,
+                               winner_total(options, evaluated, is_dealer, draw),
+                               yaku,
+                               evaluated.node()));
+    }
+    Ok(text)
+}
+
+/// 最も高い解釈と、ツモ和了か否か、和了者が受け取る点数を返す (役がない場合はエラー)
+pub fn best(options: &Options) -> Result<(Evaluated, bool, u32), failure::Error> {
+    let hand = Hand::from_str_with_rules(&options.hand, &options.rules)?;
+    let draw = draw(options, &hand)?;
+    let evaluator = evaluator(options);
+    let evaluated = evaluate(options, &evaluator, &hand, draw)?
+        .ok_or_else(|| format_err!("役がありません: {}", hand))?;
+    let points = winner_total(options, &evaluated, is_dealer(options), draw);
+    Ok((evaluated, draw, points))
+}
+
+/// `best`で採用された解釈の符の内訳
+pub fn fu_breakdown(options: &Options, evaluated: &Evaluated, draw: bool) -> Result<Vec<(&'static str, Fu)>, failure::Error> {
+    let hand = Hand::from_str_with_rules(&options.hand, &options.rules)?;
+    Ok(evaluator(options).fu_breakdown(&ParsedHand::new(&hand), draw, evaluated))
+}
+
+/// 和了形の点数を計算する
+pub fn score(options: &Options) -> Result<String, failure::Error> {
+    let hand = Hand::from_str_with_rules(&options.hand, &options.rules)?;
+    let draw = draw(options, &hand)?;
+    let evaluator = evaluator(options);
+    let evaluated = evaluate(options, &evaluator, &hand, draw)?
+        .ok_or_else(|| format_err!("役がありません: {}", hand))?;
+    let is_dealer = is_dealer(options);
+    let report = Report::new(&hand, &[], is_dealer);
+
+    match options.format {
+        Format::Json => Ok(format!("{{\"result\":{},\"honba\":{},\"riichi_sticks\":{},\"total\":{}}}",
+                                   report.json_object(&evaluated), options.honba, options.riichi_sticks,
+                                   total(options, &evaluated, is_dealer, draw))),
+        _ => {
+            let score = evaluated.score();
+            let mut text = String::new();
+            text.push_str(&format!("手牌: {}\n", hand));
+            text.push_str(&format!("面子: {}\n", evaluated.node()));
+            text.push_str(&format!("役: {}\n", evaluated.yaku_list().join(", ")));
+            match score.limit_id() {
+                Some(_) if score.han().0 >= 13 => text.push_str(&format!("{} / {}\n", score.jp_name(), score.en_name())),
+                Some(_) => text.push_str(&format!("{}{} {} / {}\n", evaluated.fu().round_up(), score.han(), score.jp_name(), score.en_name())),
+                None => text.push_str(&format!("{}{}\n", evaluated.fu().round_up(), score.han())),
+            }
+            if draw {
+                let (non_dealer, dealer) = payments(options, &evaluated, is_dealer);
+                if is_dealer {
+                    text.push_str(&format!("ツモ: {}点オール", non_dealer));
+                } else {
+                    text.push_str(&format!("ツモ: {}点 / {}点", non_dealer, dealer));
+                }
+            } else {
+                text.push_str(&format!("ロン: {}点", ron_payment(options, &evaluated, is_dealer)));
+            }
+            text.push_str(&format!(" (合計 {}点", winner_total(options, &evaluated, is_dealer, draw)));
+            if options.honba > 0 || options.riichi_sticks > 0 {
+                text.push_str(&format!(", {}本場, 供託{}本", options.honba, options.riichi_sticks));
+            }
+            text.push_str(")\n");
+            Ok(text)
+        }
+    }
+}
+
+/// 聴牌形の待ちと、それぞれの牌で和了した場合の点数を表示する
+fn waits(options: &Options) -> Result<String, failure::Error> {
+    let (tiles, open_sets, red_fives) = Hand::parse_tiles(&options.hand)?;
+    if tiles.len() + 3 * open_sets.len() != 13 {
+        return Err(format_err!("手牌は13枚で指定してください: {}", options.hand));
+    }
+    let shanten = shanten::shanten(&tiles, &open_sets);
+    let waits = if shanten == 0 { shanten::acceptance(&tiles, &open_sets) } else { Vec::new() };
+    let evaluator = evaluator(options);
+    let draw = options.draw.unwrap_or(false);
+
+    let mut json = Vec::new();
+    let mut text = format!("{}\n", if shanten == 0 { "聴牌".to_string() } else { format!("{}向聴", shanten) });
+    for (tile, remaining) in &waits {
+        let mut hand_tiles = tiles.clone();
+        hand_tiles.push(tile.clone());
+        hand_tiles.sort();
+        let hand = Hand { tiles: hand_tiles, open_sets: open_sets.clone(), winning: tile.clone(), draw: Some(draw), red_fives: red_fives.clone(), flowers: 0 };
+        let evaluated = evaluate(options, &evaluator, &hand, draw)?;
+        match &evaluated {
+            Some(evaluated) => {
+                let points = winner_total(options, evaluated, is_dealer(options), draw);
+                text.push_str(&format!("{} 残り{}枚: {} {}点\n", tile.mpsz(), remaining, evaluated.yaku_list().join(", "), points));
+            }
+            None => text.push_str(&format!("{} 残り{}枚: 役なし\n", tile.mpsz(), remaining)),
+        }
+        let result = match &evaluated {
+            Some(evaluated) => Report::new(&hand, &[], is_dealer(options)).json_object(evaluated),
+            None => "null".to_string(),
+        };
+        json.push(format!("{{\"tile\":{},\"remaining\":{},\"result\":{}}}", json_string(&tile.mpsz()), remaining, result));
+    }
+
+    match options.format {
+        Format::Json => Ok(format!("{{\"shanten\":{},\"waits\":[{}]}}", shanten, json.join(","))),
+        _ => Ok(text),
+    }
+}
+
+/// 向聴数を表示する
+fn shanten(options: &Options) -> Result<String, failure::Error> {
+    let (tiles, open_sets, _) = Hand::parse_tiles(&options.hand)?;
+    match (tiles.len() + 3 * open_sets.len()) % 3 {
+        0 => return Err(format_err!("手牌の枚数が不正です: {}", options.hand)),
+        _ if tiles.len() + 3 * open_sets.len() > 14 => return Err(format_err!("多牌です: {}", options.hand)),
+        _ => {}
+    }
+    let shanten = shanten::shanten(&tiles, &open_sets);
+    match options.format {
+        Format::Json => Ok(format!("{{\"shanten\":{}}}", shanten)),
+        _ => Ok(match shanten {
+            -1 => "和了\n".to_string(),
+            0 => "聴牌\n".to_string(),
+            n => format!("{}向聴\n", n),
+        }),
+    }
+}
+
+/// 打牌候補と有効牌を表示する
+fn discard(options: &Options) -> Result<String, failure::Error> {
+    let (tiles, open_sets, _) = Hand::parse_tiles(&options.hand)?;
+    if tiles.len() + 3 * open_sets.len() != 14 {
+        return Err(format_err!("手牌は14枚で指定してください: {}", options.hand));
+    }
+    let discards = shanten::discards(&tiles, &open_sets);
+    match options.format {
+        Format::Json => {
+            let discards: Vec<String> = discards.iter().map(|discard| {
+                let acceptance: Vec<String> = discard.acceptance.iter()
+                    .map(|(tile, remaining)| format!("{{\"tile\":{},\"remaining\":{}}}", json_string(&tile.mpsz()), remaining))
+                    .collect();
+                format!("{{\"tile\":{},\"shanten\":{},\"count\":{},\"acceptance\":[{}]}}",
+                        json_string(&discard.tile.mpsz()), discard.shanten, discard.count(), acceptance.join(","))
+            }).collect();
+            Ok(format!("[{}]", discards.join(",")))
+        }
+        _ => Ok(discards.iter().map(|discard| {
+            let acceptance: Vec<String> = discard.acceptance.iter().map(|(tile, _)| tile.mpsz()).collect();
+            let shanten = if discard.shanten == 0 { "聴牌".to_string() } else { format!("{}向聴", discard.shanten) };
+            format!("打{} {} {}種{}枚: {}\n", discard.tile.mpsz(), shanten, discard.acceptance.len(), discard.count(), acceptance.join(" "))
+        }).collect()),
+    }
+}
+
+/// 点数から翻数と符を逆引きする
+///
+/// `7700`のような和了者が受け取る点数(ツモは合計)のほか、ツモの支払い`1300-2600`、`2600all`も受け付ける
+fn lookup(options: &Options) -> Result<String, failure::Error> {
+    let is_dealer = is_dealer(options);
+    let amount = options.hand.replace(' ', "").to_ascii_lowercase();
+    let number = |s: &str| s.parse::<u32>().map_err(|_| format_err!("点数が不正です: {}", options.hand));
+    let (entries, draw) = if let Some(each) = amount.strip_suffix("all") {
+        if !is_dealer {
+            return Err(format_err!("`all`は親のツモ和了でのみ指定できます (--dealer)"));
+        }
+        (table::lookup_tsumo((number(each)?, 0), true, options.rules.kiriage), true)
+    } else if let Some(i) = amount.find('-') {
+        (table::lookup_tsumo((number(&amount[..i])?, number(&amount[i + 1..])?), false, options.rules.kiriage), true)
+    } else {
+        let draw = options.draw.unwrap_or(false);
+        (table::lookup(number(&amount)?, is_dealer, draw, options.rules.kiriage), draw)
+    };
+
+    let name = |entry: &Entry| match entry.fu {
+        Some(fu) => format!("{}{}", entry.han, fu),
+        None => table::limit_name(&entry.score),
+    };
+    match options.format {
+        Format::Json => {
+            let entries: Vec<String> = entries.iter().map(|entry| {
+                format!("{{\"han\":{},\"fu\":{},\"limit\":{},\"points\":{}}}",
+                        entry.han.0,
+                        entry.fu.map_or("null".to_string(), |fu| fu.0.to_string()),
+                        entry.score.limit_id().map_or("null".to_string(), json_string),
+                        entry.score.points(is_dealer, draw))
+            }).collect();
+            Ok(format!("[{}]", entries.join(",")))
+        }
+        _ if entries.is_empty() => Err(format_err!("該当する翻数と符がありません: {}", options.hand)),
+        _ => Ok(entries.iter().map(|entry| format!("{}\n", name(entry))).collect()),
+    }
+}
+
+/// 基準エージェント同士で対局して成績を表示する (三人麻雀のルールでは3人)
+fn simulate(options: &Options) -> Result<String, failure::Error> {
+    if options.format != Format::Text {
+        return Err(format_err!("simulateはtextで出力します"));
+    }
+    let games: usize = options.hand.trim().parse().map_err(|_| format_err!("対局数が不正です: {}", options.hand))?;
+    let config = if options.rules.sanma { MatchConfig::sanma() } else { MatchConfig::default() };
+    let config = MatchConfig { rules: options.rules.clone(), ..config };
+    let mut agents: Vec<Box<dyn Agent>> = (0..config.players).map(|_| Box::new(BaselineAgent) as Box<dyn Agent>).collect();
+    let stats = Simulator::new(config).run(&mut agents, games, options.seed)?;
+    Ok(simulator::stats_table(&stats))
+}
+
+/// 基準エージェント同士で1対局して牌譜を表示する
+fn record(options: &Options) -> Result<String, failure::Error> {
+    if options.format != Format::Text {
+        return Err(format_err!("recordはtextで出力します"));
+    }
+    let config = if options.rules.sanma { MatchConfig::sanma() } else { MatchConfig::default() };
+    let config = MatchConfig { rules: options.rules.clone(), ..config };
+    let mut agents: Vec<BaselineAgent> = vec![BaselineAgent; config.players];
+    let mut seated: Vec<&mut dyn Agent> = agents.iter_mut().map(|agent| agent as &mut dyn Agent).collect();
+    let result = Simulator::new(config).play(&mut seated, options.seed)?;
+    Ok(result.record.to_string())
+}
+
+/// 牌譜を再生し、局ごとに行動の数と結果を表示する (一致しない局があればエラー)
+fn replay(options: &Options) -> Result<String, failure::Error> {
+    if options.format != Format::Text {
+        return Err(format_err!("replayはtextで出力します"));
+    }
+    let text = if options.hand == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(&options.hand).map_err(|error| format_err!("{}を開けません: {}", options.hand, error))?
+    };
+    let record = Record::from_str(&text)?;
+    let mut output = String::new();
+    for (i, round) in record.rounds.iter().enumerate() {
+        round.verify().map_err(|error| format_err!("{}局目: {}", i + 1, error))?;
+        let wind = ["東", "南", "西", "北"][round.config.prevalent_wind.clone().tile().mpsz_parts().0 as usize - 1];
+        let results: Vec<String> = round.events.iter()
+            .filter(|event| matches!(event, Event::Win { .. } | Event::ExhaustiveDraw { .. } | Event::AbortiveDraw(_)))
+            .map(|event| event.to_string())
+            .collect();
+        output.push_str(&format!("{:>3}  {}{}局 {}本場  {:>3}手  {}\n",
+                                 i + 1, wind, round.config.dealer + 1, round.config.honba, round.steps(), results.join(" / ")));
+    }
+    output.push_str(&format!("{}局を検証しました\n", record.rounds.len()));
+    Ok(output)
+}
+
+/// 翻数×符の点数表
+fn table(options: &Options) -> Result<String, failure::Error> {
+    let table = ScoreTable::new(is_dealer(options), options.rules.kiriage);
+    match options.format {
+        Format::Json => Err(format_err!("tableはtext, csv, markdownのいずれかで出力します")),
+        Format::Csv => Ok(table.to_csv()),
+        Format::Markdown => Ok(table.to_markdown()),
+        Format::Text => Ok(table.to_text()),
+    }
+}
+
+/// 和了者が親か否か (`--dealer`または自風が東)
+fn is_dealer(options: &Options) -> bool {
+    options.dealer || options.seat_wind == Some(Wind::East.tile())
+}
+
+/// ツモ和了か否か (オプションと譜面の指定が矛盾する場合はエラー)
+fn draw(options: &Options, hand: &Hand) -> Result<bool, failure::Error> {
+    match (options.draw, hand.draw) {
+        (Some(draw), Some(written)) if draw != written => Err(format_err!("ツモ/ロンの指定が譜面と矛盾しています: {}", hand)),
+        (Some(draw), _) | (None, Some(draw)) => Ok(draw),
+        (None, None) => Ok(false),
+    }
+}
+
+fn evaluator(options: &Options) -> Evaluator {
+    let dora: Vec<Tile> = options.dora_indicators.iter().map(|tile| options.rules.dora(tile)).collect();
+    let ura_dora: Vec<Tile> = options.ura_dora_indicators.iter().map(|tile| options.rules.dora(tile)).collect();
+    let mut evaluator = Evaluator::new(options.prevalent_wind.clone(), options.seat_wind.clone(), dora, ura_dora);
+    evaluator.set_rules(options.rules.clone());
+    evaluator.set_nukidora(options.nukidora);
+    evaluator.set_dealer(options.dealer);
+    evaluator
+}
+
+/// 状況役を検証して最も高い解釈を返す (役がない場合は`None`)
+fn evaluate(options: &Options, evaluator: &Evaluator, hand: &Hand, draw: bool) -> Result<Option<Evaluated>, failure::Error> {
+    let evaluated = evaluate_all(options, evaluator, hand, draw)?.pop();
+    Ok(evaluated.filter(|evaluated| evaluated.score().han().0 > 0))
+}
+
+/// 状況役を検証して全ての解釈を返す (安い順)
+fn evaluate_all(options: &Options, evaluator: &Evaluator, hand: &Hand, draw: bool) -> Result<Vec<Evaluated>, failure::Error> {
+    let closed = hand.open_sets.iter().all(|open_set| matches!(open_set, OpenSet::ConcealedKong(_)));
+    let ready = options.riichi || options.double_riichi;
+    if options.riichi && options.double_riichi {
+        return Err(format_err!("--riichiと--double-riichiは同時に指定できません"));
+    }
+    if ready && !closed {
+        return Err(format_err!("鳴いている手牌では立直できません"));
+    }
+    if !ready && (options.ippatsu || !options.ura_dora_indicators.is_empty()) {
+        return Err(format_err!("一発と裏ドラは立直時のみ指定できます"));
+    }
+    if !draw && (options.last_tile || options.dead_wall_draw) {
+        return Err(format_err!("海底摸月と嶺上開花はツモ和了時のみ指定できます"));
+    }
+    if draw && (options.last_discard || options.robbing_a_quad) {
+        return Err(format_err!("河底撈魚と槍槓はロン和了時のみ指定できます"));
+    }
+    if options.nukidora > 0 && !options.rules.nukidora {
+        return Err(format_err!("北抜きドラは三人麻雀のルールでのみ指定できます"));
+    }
+    evaluator.validate_winds()?;
+
+    let mut situation = Vec::new();
+    if options.riichi {
+        situation.push(SituationYaku::ready());
+    }
+    if options.double_riichi {
+        situation.push(SituationYaku::double_ready());
+    }
+    if options.ippatsu {
+        situation.push(SituationYaku::one_shot());
+    }
+    if draw && closed {
+        situation.push(SituationYaku::self_pick());
+    }
+    if options.last_tile {
+        situation.push(SituationYaku::last_tile_from_the_wall());
+    }
+    if options.last_discard {
+        situation.push(SituationYaku::last_discard());
+    }
+    if options.dead_wall_draw {
+        situation.push(SituationYaku::dead_wall_draw());
+    }
+    if options.robbing_a_quad {
+        situation.push(SituationYaku::robbing_a_quad());
+    }
+
+    Ok(evaluator.evaluate_all(&ParsedHand::new(hand), draw, &situation))
+}
+
+/// 和了による各席の点数の増減 (親を席0とし、子の和了は席1、ロンは和了者の下家からとする)
+fn deltas(options: &Options, evaluated: &Evaluated, is_dealer: bool, draw: bool) -> Vec<i32> {
+    let players = if options.rules.sanma { 3 } else { 4 };
+    let stakes = Stakes::new(players, 0, options.honba, options.riichi_sticks, &options.rules);
+    let winner = if is_dealer { 0 } else { 1 };
+    stakes.win(evaluated.score(), winner, if draw { None } else { Some(winner + 1) })
+}
+
+/// ツモ和了時の支払い (子の支払い, 親の支払い) に積み棒を加えたもの
+///
+/// 和了者が親の場合、親の支払いは0となる。三人麻雀でツモ損なしの場合は北家の分を含む
+fn payments(options: &Options, evaluated: &Evaluated, is_dealer: bool) -> (u32, u32) {
+    let deltas = deltas(options, evaluated, is_dealer, true);
+    (-deltas[2] as u32, if is_dealer { 0 } else { -deltas[0] as u32 })
+}
+
+/// ロン和了時に放銃者が支払う点数 (積み棒を含む)
+fn ron_payment(options: &Options, evaluated: &Evaluated, is_dealer: bool) -> u32 {
+    let deltas = deltas(options, evaluated, is_dealer, false);
+    -deltas[if is_dealer { 1 } else { 2 }] as u32
+}
+
+/// 和了者が受け取る点数 (積み棒と供託を含む)
+fn winner_total(options: &Options, evaluated: &Evaluated, is_dealer: bool, draw: bool) -> u32 {
+    deltas(options, evaluated, is_dealer, draw)[if is_dealer { 0 } else { 1 }] as u32
+}
+
+/// 積み棒と供託を含む支払いのJSON
+fn total(options: &Options, evaluated: &Evaluated, is_dealer: bool, draw: bool) -> String {
+    let (non_dealer, dealer) = payments(options, evaluated, is_dealer);
+    format!("{{\"ron\":{},\"tsumo\":{{\"non_dealer\":{},\"dealer\":{}}},\"winner\":{}}}",
+            ron_payment(options, evaluated, is_dealer), non_dealer, dealer, winner_total(options, evaluated, is_dealer, draw))
+}
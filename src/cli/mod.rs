@@ -0,0 +1,48 @@
+//! `mahjong`コマンド
+
+pub mod args;
+pub mod batch;
+pub mod commands;
+pub mod repl;
+
+use args::{Command, Options};
+
+/// 引数を読み取ってコマンドを実行し、終了コードを返す
+///
+/// 入力が不正な場合はエラーを標準エラー出力に書き出し、1を返す。引数がない場合は使い方を表示して2を返す
+pub fn run(args: &[String]) -> i32 {
+    if args.is_empty() {
+        eprint!("{}", args::usage());
+        return 2;
+    }
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!("`mahjong help`で使い方を表示します");
+            return 2;
+        }
+    };
+    match options.command {
+        Command::Repl => return repl::run(options),
+        Command::Batch => return batch::run(&options),
+        _ => {}
+    }
+    match commands::execute(&options) {
+        Ok(output) => {
+            if output.ends_with('\n') {
+                print!("{}", output);
+            } else {
+                println!("{}", output);
+            }
+            0
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,149 @@
+use crate::cli::args::{Format, Options};
+use crate::cli::commands;
+use mahjong::groups::TilesNewType;
+use mahjong::tiles::Tile;
+use std::io::{BufRead, IsTerminal, Write};
+
+/// 対話モードの使い方
+fn usage() -> &'static str {
+    "\
+手牌を入力すると点数と全ての解釈を表示します (例: 234m234p234s88p567s+7s --riichi)
+オプションだけを入力すると、卓の状況を変更して直前の手牌を計算し直します
+  --round, --seat, --dora, --honba, --sticks, --rules  卓の状況 (次の手牌にも引き継ぐ)
+  --riichi, --tsumo, --ron, --ippatsu など             和了の状況 (手牌を入力すると取り消す)
+  --no-riichi のように --no- を付けると取り消します
+:context  現在の状況を表示する
+:clear    ドラ表示牌を取り消す
+:reset    全ての状況を取り消す
+:help     この説明を表示する
+:quit     終了する
+"
+}
+
+/// 対話モードの状態
+pub struct Session {
+    /// 卓と和了の状況
+    options: Options,
+    /// 直前に入力した手牌
+    last_hand: Option<String>,
+}
+
+impl Session {
+    pub fn new(options: Options) -> Self {
+        let last_hand = if options.hand.is_empty() { None } else { Some(options.hand.clone()) };
+        Session { options: Options { format: Format::Text, ..options }, last_hand }
+    }
+
+    /// 1行を処理して出力を返す (`None`の場合は終了する)
+    pub fn eval_line(&mut self, line: &str) -> Option<Result<String, failure::Error>> {
+        let line = line.trim();
+        match line {
+            "" => return Some(Ok(String::new())),
+            ":quit" | ":q" | ":exit" => return None,
+            ":help" => return Some(Ok(usage().to_string())),
+            ":context" => return Some(Ok(self.context())),
+            ":clear" => {
+                self.options.dora_indicators.clear();
+                self.options.ura_dora_indicators.clear();
+                return Some(Ok(self.context()));
+            }
+            ":reset" => {
+                self.options = Options { command: self.options.command, ..Options::default() };
+                self.last_hand = None;
+                return Some(Ok(self.context()));
+            }
+            _ if line.starts_with(':') => return Some(Err(format_err!("不明なコマンドです: {}", line))),
+            _ => {}
+        }
+
+        let tokens: Vec<String> = line.split_whitespace().map(String::from).collect();
+        let mut options = self.options.clone();
+        if tokens.iter().any(|token| token == "--tsumo" || token == "--ron") {
+            options.draw = None;
+        }
+        let mut hand = match options.apply(&tokens) {
+            Ok(hand) => hand,
+            Err(error) => return Some(Err(error)),
+        };
+        // 手牌を入力した場合は和了の状況を取り消してから反映し直す
+        let is_hand = !hand.is_empty();
+        if is_hand {
+            options = self.options.clone();
+            options.reset_situation();
+            hand = match options.apply(&tokens) {
+                Ok(hand) => hand,
+                Err(error) => return Some(Err(error)),
+            };
+        }
+        self.options = options;
+        if is_hand {
+            self.last_hand = Some(hand);
+        }
+        match &self.last_hand {
+            Some(hand) => Some(self.evaluate(hand)),
+            None => Some(Ok(self.context())),
+        }
+    }
+
+    /// 点数と全ての解釈
+    fn evaluate(&self, hand: &str) -> Result<String, failure::Error> {
+        let options = Options { hand: hand.to_string(), ..self.options.clone() };
+        let mut text = commands::score(&options).unwrap_or_else(|error| format!("{}\n", error));
+        text.push_str(&commands::interpretations(&options)?);
+        Ok(text)
+    }
+
+    /// 現在の状況
+    fn context(&self) -> String {
+        let options = &self.options;
+        let wind = |wind: &Option<Tile>| wind.as_ref().map_or("-".to_string(), |tile| tile.mpsz());
+        let tiles = |tiles: &Vec<Tile>| if tiles.is_empty() {
+            "-".to_string()
+        } else {
+            TilesNewType(tiles.clone()).to_mpsz(&mut Vec::new())
+        };
+        let mut situation = Vec::new();
+        [(options.riichi, "立直"), (options.double_riichi, "ダブル立直"), (options.ippatsu, "一発"),
+            (options.draw == Some(true), "ツモ"), (options.draw == Some(false), "ロン"),
+            (options.last_tile, "海底"), (options.last_discard, "河底"),
+            (options.dead_wall_draw, "嶺上"), (options.robbing_a_quad, "槍槓")]
+            .iter().filter(|(on, _)| *on).for_each(|(_, name)| situation.push(*name));
+        format!("場風: {} 自風: {} ドラ表示牌: {} 裏ドラ表示牌: {} {}本場 供託{}本 状況: {}\n",
+                wind(&options.prevalent_wind), wind(&options.seat_wind),
+                tiles(&options.dora_indicators), tiles(&options.ura_dora_indicators),
+                options.honba, options.riichi_sticks,
+                if situation.is_empty() { "-".to_string() } else { situation.join(", ") })
+    }
+}
+
+/// 標準入力から1行ずつ読み取って処理する
+///
+/// 標準入力が端末の場合のみプロンプトを表示する。エラーがあっても終了しない
+pub fn run(options: Options) -> i32 {
+    let mut session = Session::new(options);
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        print!("{}", usage());
+    }
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("mahjong> ");
+            let _ = std::io::stdout().flush();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(error)) => {
+                eprintln!("error: {}", error);
+                return 1;
+            }
+            None => return 0,
+        };
+        match session.eval_line(&line) {
+            Some(Ok(output)) => print!("{}", output),
+            Some(Err(error)) => eprintln!("error: {}", error),
+            None => return 0,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::cli::args::{Command, Format, Options};
+use crate::cli::batch::{self, Expected};
+use crate::cli::commands;
+use crate::cli::repl::Session;
+use mahjong::tiles::Wind;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|arg| arg.to_string()).collect()
+}
+
+fn parse(line: &str) -> Options {
+    Options::parse(&args(line)).unwrap()
+}
+
+fn execute(line: &str) -> Result<String, failure::Error> {
+    commands::execute(&parse(line))
+}
+
+#[test]
+fn options() {
+    let options = parse("score 234m234p234s88p567s 7s --seat=e --round s --dora 3m7z --riichi --tsumo --honba 2 --format json");
+    assert_eq!(options.command, Command::Score);
+    // 手牌以外の引数は空白区切りで連結する
+    assert_eq!(options.hand, "234m234p234s88p567s 7s");
+    assert_eq!(options.seat_wind, Some(Wind::East.tile()));
+    assert_eq!(options.prevalent_wind, Some(Wind::South.tile()));
+    assert_eq!(options.dora_indicators.len(), 2);
+    assert!(options.riichi);
+    assert_eq!(options.draw, Some(true));
+    assert_eq!(options.honba, 2);
+    assert_eq!(options.format, Format::Json);
+
+    // `--no-`で取り消す
+    let options = parse("score 123m --riichi --dealer --no-riichi --rules classic");
+    assert!(!options.riichi);
+    assert!(options.dealer);
+    assert!(!options.rules.open_tanyao);
+
+    // 手牌が不要なコマンド
+    assert_eq!(parse("table --dealer").command, Command::Table);
+    assert_eq!(parse("").command, Command::Help);
+
+    for line in &[
+        "score",
+        "unknown 123m",
+        "score 123m --unknown",
+        "score 123m --honba",
+        "score 123m --honba x",
+        "score 123m --tsumo --ron",
+        "score 123m --rules unknown",
+        "score 123m --format xml",
+        "score 123m --riichi=false",
+        "score 123m --tsumo=no",
+        "score 123m --no-ippatsu=true",
+    ] {
+        assert!(Options::parse(&args(line)).is_err(), "{}", line);
+    }
+}
+
+#[test]
+fn score_command() {
+    // 自風を指定しなくても`--dealer`で親の点数になる
+    let text = execute("score 234m234p234s88p567s+7s --dealer").unwrap();
+    assert!(text.ends_with("ロン: 11600点 (合計 11600点)\n"), "{}", text);
+    assert_eq!(execute("score 234m234p234s88p567s+7s --dealer").unwrap(),
+               execute("score 234m234p234s88p567s+7s --seat e").unwrap());
+    let text = execute("score 234m234p234s88p567s+7s").unwrap();
+    assert!(text.ends_with("ロン: 7700点 (合計 7700点)\n"), "{}", text);
+
+    // 喰いタンなしのルールでは鳴いた断么九だけでは役にならない
+    assert!(execute("score 345m456p88p67s[234s]+8s").is_ok());
+    assert!(execute("score 345m456p88p67s[234s]+8s --rules classic").is_err());
+
+    assert!(execute("score 234m234p234s88p567s+7s --format csv").is_err());
+}
+
+#[test]
+fn score_sanma() {
+    // 三人麻雀のツモ和了はツモ損ありなら2人分のみ受け取る
+    let hand = "score 123p456p789p1s1s111z --tsumo --rules sanma";
+    assert!(execute(hand).unwrap().ends_with("ツモ: 1000点 / 2000点 (合計 3000点)\n"));
+    assert!(execute(&format!("{} --no-tsumo-loss", hand)).unwrap().ends_with("ツモ: 1500点 / 2500点 (合計 4000点)\n"));
+    assert!(execute(&format!("{} --seat e", hand)).unwrap().ends_with("ツモ: 3900点オール (合計 7800点)\n"));
+    assert!(execute("score 123p456p789p1s1s111z --tsumo").unwrap().ends_with("ツモ: 1000点 / 2000点 (合計 4000点)\n"));
+    let json = execute(&format!("{} --format json --sticks 1", hand)).unwrap();
+    assert!(json.ends_with("\"total\":{\"ron\":3900,\"tsumo\":{\"non_dealer\":1000,\"dealer\":2000},\"winner\":4000}}"), "{}", json);
+}
+
+#[test]
+fn repl() {
+    let mut session = Session::new(parse("repl"));
+    let mut eval = |line: &str| session.eval_line(line).map(|result| result.map_err(|error| error.to_string()));
+
+    assert_eq!(eval(""), Some(Ok(String::new())));
+    assert!(eval(":help").unwrap().unwrap().contains(":quit"));
+
+    // 手牌を入力すると点数と全ての解釈を表示する
+    let text = eval("234m234p234s88p567s+7s --riichi").unwrap().unwrap();
+    assert!(text.contains("ロン: 8000点"), "{}", text);
+    assert!(text.contains("   翻  符    点数"), "{}", text);
+
+    // オプションだけを入力すると直前の手牌を計算し直す
+    let text = eval("--seat e --tsumo").unwrap().unwrap();
+    assert!(text.contains("ツモ: 6000点オール"), "{}", text);
+    let text = eval(":context").unwrap().unwrap();
+    assert!(text.contains("自風: 1z") && text.contains("状況: 立直, ツモ"), "{}", text);
+
+    // 手牌を入力すると和了の状況は取り消し、卓の状況は引き継ぐ
+    eval("--dora 1m --honba 1").unwrap().unwrap();
+    eval("234m234p234s88p567s+7s").unwrap().unwrap();
+    let text = eval(":context").unwrap().unwrap();
+    assert!(text.contains("自風: 1z ドラ表示牌: 1m 裏ドラ表示牌: - 1本場") && text.contains("状況: -"), "{}", text);
+    let text = eval(":clear").unwrap().unwrap();
+    assert!(text.contains("ドラ表示牌: -"), "{}", text);
+
+    // 不正な入力はエラーを返し、状況は変えない
+    assert!(eval(":unknown").unwrap().is_err());
+    assert!(eval("--unknown").unwrap().is_err());
+    assert!(eval("--honba x").unwrap().is_err());
+    assert!(eval(":context").unwrap().unwrap().contains("1本場"));
+
+    let text = eval(":reset").unwrap().unwrap();
+    assert!(text.starts_with("場風: - 自風: - ドラ表示牌: - 裏ドラ表示牌: - 0本場"), "{}", text);
+    assert!(eval("--riichi").unwrap().unwrap().contains("状況: 立直"));
+
+    assert_eq!(eval(":quit"), None);
+}
+
+#[test]
+fn batch_expected() {
+    let expected = Expected::parse(" han=8 fu=20 points=16000 limit=baiman yaku=ready_hand,,self_pick ").unwrap();
+    assert_eq!((expected.han, expected.fu, expected.points), (Some(8), Some(20), Some(16000)));
+    assert_eq!(expected.limit, Some("baiman".to_string()));
+    assert_eq!(expected.yaku, Some(vec!["ready_hand".to_string(), "self_pick".to_string()]));
+    assert!(Expected::parse("").unwrap().han.is_none());
+    for s in &["han", "han=x", "fu=-1", "dora=1"] {
+        assert!(Expected::parse(s).is_err(), "{}", s);
+    }
+
+    let base = parse("batch -");
+    let line = "234m234p234s88p567s+7s --riichi --tsumo --dora 7p";
+    let yaku = "yaku=ready_hand,self_pick,all_simple,no_points_hand,three_colour_straight,dora";
+    assert!(batch::check(&base, &format!("{} | han=8 fu=20 points=16000 limit=baiman {}", line, yaku)).unwrap().is_empty());
+    assert!(batch::check(&base, line).unwrap().is_empty());
+    // 期待値と異なる項目ごとに差分を返す
+    assert_eq!(batch::check(&base, &format!("{} | han=7 fu=20 points=12000 limit=haneman yaku=ready_hand,seven_pairs", line)).unwrap(), vec![
+        "han: expected 7, actual 8".to_string(),
+        "points: expected 12000, actual 16000".to_string(),
+        "limit: expected haneman, actual baiman".to_string(),
+        "yaku: -seven_pairs +self_pick +all_simple +no_points_hand +three_colour_straight +dora".to_string(),
+    ]);
+    // 符の不一致には採用した解釈の符の内訳を添える
+    let diffs = batch::check(&base, "[111z]234m234p567s88p+8p --round E --seat S | fu=40").unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert!(diffs[0].starts_with("fu: expected 40, actual 30 (base 20 + melds 4 + wait 2 = 26; "), "{}", diffs[0]);
+    let diffs = batch::check(&base, &format!("{} | fu=30", line)).unwrap();
+    assert!(diffs[0].starts_with("fu: expected 30, actual 20 (fixed 20 = 20; "), "{}", diffs[0]);
+    // コマンドラインの指定は行の指定で上書きする
+    let base = parse("batch - --ron");
+    assert!(batch::check(&base, &format!("{} | points=16000", line)).unwrap().is_empty());
+    assert!(batch::check(&base, "234m234p234s88p567s+7s | han=x").is_err());
+    assert!(batch::check(&base, "234m234p234s88p567s+7s --unknown").is_err());
+    assert!(batch::check(&base, "123m456p789s11z23z+1z").is_err());
+}
+
+#[test]
+fn batch_exit_code() {
+    let path = std::env::temp_dir().join(format!("mahjong-batch-{}.txt", std::process::id()));
+    let run = |content: &str| {
+        std::fs::write(&path, content).unwrap();
+        batch::run(&parse(&format!("batch {}", path.display())))
+    };
+    assert_eq!(run("# コメント\n\n234m234p234s88p567s+7s | points=7700\n"), 0);
+    assert_eq!(run("234m234p234s88p567s+7s | points=8000\n"), 1);
+    assert_eq!(run("234m234p234s88p567s+7s | points=7700\nabc\n"), 1);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(batch::run(&parse(&format!("batch {}", path.display()))), 1);
+}
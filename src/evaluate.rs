@@ -5,8 +5,10 @@ use crate::groups::{Tiles, OpenSet, Set, Sets, Hand};
 use crate::yaku::hand::{HandYaku, Yakuman};
 use crate::score::{Fu, Score, Han};
 use crate::yaku::YakuAttributes;
+use crate::rules::Rules;
 
 use std::fmt::{Display, Formatter, Error};
+use std::cmp::Ordering;
 pub use std::str::FromStr;
 
 pub struct Evaluator {
@@ -24,6 +26,12 @@ pub struct Evaluator {
     dora: Vec<Tile>,
     /// 裏ドラ
     ura_dora: Vec<Tile>,
+    /// 抜いた北の枚数
+    nukidora: u32,
+    /// 自風に関わらず親として扱うか
+    dealer: bool,
+    /// 採用するルール
+    rules: Rules,
 }
 
 impl Evaluator {
@@ -31,7 +39,7 @@ impl Evaluator {
         let adopted_yaku_list = Self::default_adopted_yaku_list(&seat_wind, &prevalent_wind);
         let adopted_yakuman_list = Self::default_adopted_yakuman_list();
 
-        Self { situation: Vec::new(), adopted_yakuman_list, adopted_yaku_list, prevalent_wind, seat_wind, dora, ura_dora }
+        Self { situation: Vec::new(), adopted_yakuman_list, adopted_yaku_list, prevalent_wind, seat_wind, dora, ura_dora, nukidora: 0, dealer: false, rules: Rules::standard() }
     }
 
     pub fn default_adopted_yaku_list(seat_wind: &Option<Tile>, prevalent_wind: &Option<Tile>) -> Vec<HandYaku> {
@@ -119,7 +127,7 @@ impl Evaluator {
                                   open_sets.iter().all(|open| !open.contains_yaotyu()) {
                                   Some(Han(1))
                               } else { None }
-                          }), None);
+                          }), None).as_all_simple();
         let three_colour_straight =
             HandYaku::new("三色同順 / Three colour straight", None,
                           Box::new(|candidate: &Wait| {
@@ -253,7 +261,7 @@ impl Evaluator {
                               });
                               candidate.node().open_sets.iter().for_each(|set| {
                                   match set {
-                                      OpenSet::Pung(_) | OpenSet::Kong(_) | OpenSet::ConcealedKong(_) => {
+                                      OpenSet::Pung(..) | OpenSet::Kong(..) | OpenSet::AddedKong(..) | OpenSet::ConcealedKong(_) => {
                                           if let Some(tile) = set.sum_tile() {
                                               pong_sums.push(tile);
                                           }
@@ -529,18 +537,67 @@ impl Evaluator {
 }
 
 impl Evaluator {
+    /// 採用するルールを変更する
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// 抜いた北の枚数を設定する (1枚につき1翻、北がドラの場合はさらに加える)
+    pub fn set_nukidora(&mut self, count: u32) {
+        self.nukidora = count;
+    }
+
+    /// 自風が指定されていなくても親として扱う
+    pub fn set_dealer(&mut self, dealer: bool) {
+        self.dealer = dealer;
+    }
+
+    /// 場風と自風がルールの人数で有り得るか (三人麻雀では北家はなく、北場もない)
+    pub fn validate_winds(&self) -> Result<(), failure::Error> {
+        let north = Some(Wind::North.tile());
+        if self.rules.sanma && (self.seat_wind == north || self.prevalent_wind == north) {
+            return Err(format_err!("三人麻雀では場風と自風に北を指定できません"));
+        }
+        Ok(())
+    }
+
+    /// 親か否か (`set_dealer`で指定したか自風が東)
+    pub fn is_dealer(&self) -> bool {
+        self.dealer || self.seat_wind == Some(Wind::East.tile())
+    }
+
+    /// 最も高い解釈を返す
     pub fn evaluate(&self, parsed_hand: &ParsedHand, draw: bool, situation: &Vec<SituationYaku>) -> Option<Evaluated> {
-        self.evaluate_all(parsed_hand, draw, situation).last().cloned()
+        self.evaluate_all(parsed_hand, draw, situation).pop()
     }
 
+    /// 全ての解釈を返す
+    ///
+    /// 安い順に並んでおり、最後の要素が採用される解釈となる
     pub fn evaluate_all(&self, parsed_hand: &ParsedHand, draw: bool, situation: &Vec<SituationYaku>) -> Vec<Evaluated> {
         let waits = Waits::from_vec(parsed_hand, draw, &self.prevalent_wind, &self.seat_wind);
         let mut scores: Vec<Evaluated> = waits.waits
-            .iter().map(|c| self.evaluate_wait(&waits.original_hand, c, draw, situation)).collect();
-        scores.sort_by(|a, b| a.score.score(false).cmp(&b.score.score(false)));
+            .iter().map(|c| self.evaluate_wait(&waits.original_hand, c, draw, situation, parsed_hand.red_fives, parsed_hand.flowers)).collect();
+        let is_dealer = self.is_dealer();
+        scores.sort_by(|a, b| a.cmp_by(b, is_dealer, draw));
         scores
     }
 
+    /// 採用された解釈の符の内訳
+    ///
+    /// 平和のツモや七対子のように役で符が決まる場合は`fixed`のみを返す
+    pub fn fu_breakdown(&self, parsed_hand: &ParsedHand, draw: bool, evaluated: &Evaluated) -> Vec<(&'static str, Fu)> {
+        let waits = Waits::from_vec(parsed_hand, draw, &self.prevalent_wind, &self.seat_wind);
+        waits.waits.iter()
+            .find(|wait| wait.node() == evaluated.node() && wait.fu() == evaluated.fu())
+            .map(|wait| wait.fu_breakdown(draw, &self.prevalent_wind, &self.seat_wind))
+            .unwrap_or_else(|| vec![("fixed", evaluated.fu())])
+    }
+
     fn evaluate_yaku(&self, yaku: &Box<HandYaku>, yaku_list: &mut Vec<String>, candidate: &Wait, han: &mut Han) {
         let rule = &yaku.rule;
         if let Some(han_) = rule(candidate) {
@@ -565,7 +622,7 @@ impl Evaluator {
         }
     }
 
-    fn evaluate_wait(&self, original_hand: &Vec<Tile>, wait: &Wait, draw: bool, situation: &Vec<SituationYaku>) -> Evaluated {
+    fn evaluate_wait(&self, original_hand: &Vec<Tile>, wait: &Wait, draw: bool, situation: &Vec<SituationYaku>, red_fives: u32, flowers: u32) -> Evaluated {
         let mut yakuman_list = Vec::new();
         let mut multiple = 0;
         self.adopted_yakuman_list.iter().for_each(|yakuman| {
@@ -580,7 +637,10 @@ impl Evaluator {
             }
         });
         if multiple != 0 {
-            return Evaluated { score: Score::yakuman(multiple as u8), node: wait.node().clone(), yaku_list: yakuman_list };
+            if !self.rules.multiple_yakuman {
+                multiple = 1;
+            }
+            return Evaluated { score: Score::yakuman(multiple as u8), node: wait.node().clone(), fu: wait.fu(), yaku_list: yakuman_list };
         }
 
         let mut han = Han(0);
@@ -591,6 +651,10 @@ impl Evaluator {
             han += st.han_value();
         }
         for adopted_yaku in &self.adopted_yaku_list {
+            // 喰いタンなしの場合
+            if !self.rules.open_tanyao && !wait.closed() && adopted_yaku.all_simple {
+                continue;
+            }
             let rule = &adopted_yaku.rule;
             if let Some(han_) = rule(wait) {
                 han += han_;
@@ -606,39 +670,112 @@ impl Evaluator {
             }
         }
 
+        // ドラ (役がある場合のみ数える)
+        if han != Han(0) {
+            let dora = Self::count_dora(original_hand, &self.dora);
+            if dora > 0 {
+                han += Han(dora);
+                yaku_list.push("ドラ / Dora".to_string());
+            }
+            let ura_dora = Self::count_dora(original_hand, &self.ura_dora);
+            if ura_dora > 0 {
+                han += Han(ura_dora);
+                yaku_list.push("裏ドラ / Ura dora".to_string());
+            }
+            if self.nukidora > 0 {
+                let north = [Wind::North.tile()];
+                han += Han(self.nukidora * (1 + Self::count_dora(&north, &self.dora) + Self::count_dora(&north, &self.ura_dora)));
+                yaku_list.push("抜きドラ / North dora".to_string());
+            }
+            if self.rules.red_fives && red_fives > 0 {
+                han += Han(red_fives);
+                yaku_list.push("赤ドラ / Red five".to_string());
+            }
+            if self.rules.flowers && flowers > 0 {
+                han += Han(flowers);
+                yaku_list.push("花牌 / Flower".to_string());
+            }
+        }
 
         let fu = match fu {
             Some(fu) => fu,
             None => wait.fu(),
         };
 
-        Evaluated { score: Score::new(han, fu), node: wait.node().clone(), yaku_list }
+        Evaluated { score: Score::new_with_kiriage(han, fu, self.rules.kiriage), node: wait.node().clone(), fu, yaku_list }
+    }
+
+    /// 手牌(晒した牌を含む)に含まれるドラの枚数 (同じ牌が複数指定された場合は重ねて数える)
+    fn count_dora(tiles: &[Tile], dora: &[Tile]) -> u32 {
+        dora.iter().map(|dora| tiles.iter().filter(|tile| tile == &dora).count() as u32).sum()
     }
 
+    /// 譜面でツモ/ロンが指定されている場合は`draw`より優先する
     pub fn evaluate_str(&self, string: &str, draw: bool, situation: &Vec<SituationYaku>) -> Result<Option<Evaluated>, failure::Error> {
         let hand = Hand::from_str(string)?;
         let parsed_hand = ParsedHand::new(&hand);
-        Ok(Evaluator::new(None, None, Vec::new(), Vec::new()).evaluate(&parsed_hand, draw, situation))
+        Ok(self.evaluate(&parsed_hand, hand.draw.unwrap_or(draw), situation))
     }
 
     pub fn evaluate_all_str(&self, string: &str, draw: bool, situation: &Vec<SituationYaku>) -> Result<Vec<Evaluated>, failure::Error> {
         let hand = Hand::from_str(string)?;
         let parsed_hand = ParsedHand::new(&hand);
-        Ok(Evaluator::new(None, None, Vec::new(), Vec::new()).evaluate_all(&parsed_hand, draw, situation))
+        Ok(self.evaluate_all(&parsed_hand, hand.draw.unwrap_or(draw), situation))
     }
 }
 
 /// 牌形と点数
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Evaluated {
     node: Node,
     score: Score,
+    /// 符
+    fu: Fu,
     yaku_list: Vec<String>,
 }
 
+impl Evaluated {
+    pub fn new(node: Node, score: Score, fu: Fu, yaku_list: Vec<String>) -> Self {
+        Evaluated { node, score, fu, yaku_list }
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn fu(&self) -> Fu {
+        self.fu
+    }
+
+    pub fn yaku_list(&self) -> &Vec<String> {
+        &self.yaku_list
+    }
+
+    /// 高点法による比較
+    ///
+    /// 和了者の点数、翻数、符の順に比較し、それでも並ばない場合は役と牌形で順序を決める
+    pub fn cmp_by(&self, other: &Self, is_dealer: bool, draw: bool) -> Ordering {
+        let Han(han) = self.score.han();
+        let Han(other_han) = other.score.han();
+        let Fu(fu) = self.fu;
+        let Fu(other_fu) = other.fu;
+        self.score.points(is_dealer, draw).cmp(&other.score.points(is_dealer, draw))
+            .then(han.cmp(&other_han))
+            .then(fu.cmp(&other_fu))
+            .then_with(|| self.yaku_list.cmp(&other.yaku_list))
+            .then_with(|| self.node.to_string().cmp(&other.node.to_string()))
+    }
+}
+
 impl Display for Evaluated {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        writeln!(f, "{}", self.node)?;
+        // `{:#}`の場合は牌の絵文字で出力する
+        self.node.fmt(f)?;
+        writeln!(f)?;
         writeln!(f, "{}", self.yaku_list.join(","))?;
         writeln!(f, "{}", self.score)
     }
@@ -688,21 +825,8 @@ impl Wait {
         let mut candidates = Vec::new();
 
         // 府計算
-        let mut fu = if draw { Fu(22) } else if node.open_sets.len() == 0 { Fu(30) } else { Fu(20) };
-        node.sets.iter().for_each(|set| fu += set.fu());
-        node.open_sets.iter().for_each(|set| fu += set.fu());
-
-        // 雀頭による符
-        if let Some(Set::Pair(head)) = node.sets.first() {
-            let (head1, head2) = (head.get(0), head.get(1));
-            if (head1, head2) == (prevalent_wind.as_ref(), prevalent_wind.as_ref())
-                || (head1, head2) == (seat_wind.as_ref(), seat_wind.as_ref())
-                || (head1, head2) == (Some(&Dragon::White.tile()), Some(&Dragon::White.tile()))
-                || (head1, head2) == (Some(&Dragon::Green.tile()), Some(&Dragon::Green.tile()))
-                || (head1, head2) == (Some(&Dragon::Red.tile()), Some(&Dragon::Red.tile())) {
-                fu += Fu(2);
-            }
-        }
+        let mut fu = Fu(0);
+        Wait::base_fu(node, draw, &prevalent_wind, &seat_wind).into_iter().for_each(|(_, each)| fu += each);
 
         wait_candidates.iter().for_each(|set| {
             match set {
@@ -734,6 +858,45 @@ impl Wait {
         candidates
     }
 
+    /// 待ちによらない符の内訳 (副底、ツモ、門前ロン、面子、雀頭)
+    fn base_fu(node: &Node, draw: bool, prevalent_wind: &Option<Tile>, seat_wind: &Option<Tile>) -> Vec<(&'static str, Fu)> {
+        let mut parts = vec![("base", Fu(20))];
+        if draw {
+            parts.push(("tsumo", Fu(2)));
+        } else if node.open_sets.is_empty() {
+            parts.push(("closed ron", Fu(10)));
+        }
+        let mut melds = Fu(0);
+        node.sets.iter().for_each(|set| melds += set.fu());
+        node.open_sets.iter().for_each(|set| melds += set.fu());
+        if melds != Fu(0) {
+            parts.push(("melds", melds));
+        }
+
+        // 雀頭による符
+        if let Some(Set::Pair(head)) = node.sets.first() {
+            let (head1, head2) = (head.first(), head.get(1));
+            if (head1, head2) == (prevalent_wind.as_ref(), prevalent_wind.as_ref())
+                || (head1, head2) == (seat_wind.as_ref(), seat_wind.as_ref())
+                || (head1, head2) == (Some(&Dragon::White.tile()), Some(&Dragon::White.tile()))
+                || (head1, head2) == (Some(&Dragon::Green.tile()), Some(&Dragon::Green.tile()))
+                || (head1, head2) == (Some(&Dragon::Red.tile()), Some(&Dragon::Red.tile())) {
+                parts.push(("pair", Fu(2)));
+            }
+        }
+        parts
+    }
+
+    /// 符の内訳 (`base_fu`に待ちの符を加える)
+    pub fn fu_breakdown(&self, draw: bool, prevalent_wind: &Option<Tile>, seat_wind: &Option<Tile>) -> Vec<(&'static str, Fu)> {
+        let mut parts = Wait::base_fu(self.node(), draw, prevalent_wind, seat_wind);
+        match self {
+            Wait::Kanchan(..) | Wait::Penchan(..) | Wait::Tanki(..) => parts.push(("wait", Fu(2))),
+            Wait::Ryanmen(..) | Wait::Shanpon(..) => {}
+        }
+        parts
+    }
+
     fn winning(&self) -> Tile {
         match &self {
             Wait::Ryanmen(_, _, winning) => winning,
@@ -805,19 +968,29 @@ impl Display for Wait {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         match &self {
             Wait::Ryanmen(node, fu, _) => {
-                writeln!(f, "両面待ち: Ryanmen {} / {}", fu, node)
+                write!(f, "両面待ち: Ryanmen {} / ", fu)?;
+                node.fmt(f)?;
+                writeln!(f)
             }
             Wait::Kanchan(node, fu, _) => {
-                writeln!(f, "嵌張待ち: Kanchan {} / {}", fu, node)
+                write!(f, "嵌張待ち: Kanchan {} / ", fu)?;
+                node.fmt(f)?;
+                writeln!(f)
             }
             Wait::Penchan(node, fu, _) => {
-                writeln!(f, "辺張待ち: Penchan {} / {}", fu, node)
+                write!(f, "辺張待ち: Penchan {} / ", fu)?;
+                node.fmt(f)?;
+                writeln!(f)
             }
             Wait::Shanpon(node, fu, _) => {
-                writeln!(f, "双碰待ち: Shanpon {} / {}", fu, node)
+                write!(f, "双碰待ち: Shanpon {} / ", fu)?;
+                node.fmt(f)?;
+                writeln!(f)
             }
             Wait::Tanki(node, fu, _) => {
-                writeln!(f, "単騎待ち: Tanki {} / {}", fu, node)
+                write!(f, "単騎待ち: Tanki {} / ", fu)?;
+                node.fmt(f)?;
+                writeln!(f)
             }
         }
     }
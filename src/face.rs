@@ -0,0 +1,22 @@
+//! 描画する牌の面 (SVGと端末の描画で共通)
+
+use crate::tiles::Tile;
+
+/// 描画する牌の面
+pub(crate) enum Face {
+    /// 表向き (赤五か否か)
+    Up(Tile, bool),
+    /// 裏向き
+    Down,
+}
+
+/// 赤五を割り当てて牌の面を決める
+pub(crate) fn face(tile: &Tile, red_fives: &mut Vec<Tile>) -> Face {
+    match red_fives.iter().position(|red| red == tile) {
+        Some(i) => {
+            red_fives.remove(i);
+            Face::Up(tile.clone(), true)
+        }
+        None => Face::Up(tile.clone(), false),
+    }
+}
@@ -0,0 +1,240 @@
+use crate::rng::Rng;
+use crate::round::{Round, RoundConfig, RoundResult, Seat};
+use crate::rules::Rules;
+use crate::tiles::Wind;
+
+/// 対局の長さ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    /// 東風戦
+    Tonpuusen,
+    /// 半荘戦
+    Hanchan,
+}
+
+impl Length {
+    /// 予定する場の数
+    fn winds(self) -> usize {
+        match self {
+            Length::Tonpuusen => 1,
+            Length::Hanchan => 2,
+        }
+    }
+}
+
+/// 対局の設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchConfig {
+    pub length: Length,
+    /// 人数
+    pub players: usize,
+    /// 配給原点
+    pub starting_points: i32,
+    /// 返し (オカは(返し-配給原点)×人数をトップが受け取る)
+    pub return_points: i32,
+    /// 順位ウマ (千点単位、1位から順に)
+    pub uma: Vec<i32>,
+    /// 持ち点が0点未満になったら終了するか
+    pub bust: bool,
+    /// 返しに達した人がいない場合に西入するか
+    pub west_extension: bool,
+    pub rules: Rules,
+}
+
+/// 半荘戦、4人、25000点持ち30000点返し、ウマ10-20
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            length: Length::Hanchan,
+            players: 4,
+            starting_points: 25000,
+            return_points: 30000,
+            uma: vec![20, 10, -10, -20],
+            bust: true,
+            west_extension: true,
+            rules: Rules::standard(),
+        }
+    }
+}
+
+impl MatchConfig {
+    /// 三人麻雀の半荘戦、35000点持ち40000点返し、ウマ0-15
+    pub fn sanma() -> Self {
+        MatchConfig {
+            players: 3,
+            starting_points: 35000,
+            return_points: 40000,
+            uma: vec![15, 0, -15],
+            rules: Rules::sanma(),
+            ..MatchConfig::default()
+        }
+    }
+}
+
+/// 最終順位
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    /// 順位 (1から)
+    pub rank: usize,
+    pub seat: Seat,
+    /// 持ち点
+    pub points: i32,
+    /// 返しとの差、ウマ、オカを合計したポイント (千点単位)
+    pub score: f64,
+}
+
+/// 対局の進行
+///
+/// 起家を席0とし、`next_round`で局を始めて`finish_round`で結果を反映する
+#[derive(Debug, Clone)]
+pub struct Match {
+    config: MatchConfig,
+    rng: Rng,
+    scores: Vec<i32>,
+    /// 局の通し番号 (0が東1局、人数ごとに場が進む)
+    kyoku: usize,
+    honba: u32,
+    riichi_sticks: u32,
+    finished: bool,
+}
+
+impl Match {
+    /// 各局の山はシードから作った乱数で積む
+    pub fn new(config: MatchConfig, seed: u64) -> Self {
+        let scores = vec![config.starting_points; config.players];
+        Match { config, rng: Rng::new(seed), scores, kyoku: 0, honba: 0, riichi_sticks: 0, finished: false }
+    }
+
+    pub fn config(&self) -> &MatchConfig {
+        &self.config
+    }
+
+    pub fn scores(&self) -> &Vec<i32> {
+        &self.scores
+    }
+
+    pub fn honba(&self) -> u32 {
+        self.honba
+    }
+
+    pub fn riichi_sticks(&self) -> u32 {
+        self.riichi_sticks
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 場風
+    pub fn prevalent_wind(&self) -> Wind {
+        [Wind::East, Wind::South, Wind::West, Wind::North][self.kyoku / self.config.players % 4].clone()
+    }
+
+    /// 親の席
+    pub fn dealer(&self) -> Seat {
+        self.kyoku % self.config.players
+    }
+
+    /// 局の名前 (`東1局 0本場`など)
+    pub fn round_name(&self) -> String {
+        let wind = ["東", "南", "西", "北"][self.kyoku / self.config.players % 4];
+        format!("{}{}局 {}本場", wind, self.dealer() + 1, self.honba)
+    }
+
+    /// 次の局の開始時の状況
+    pub fn round_config(&self) -> RoundConfig {
+        RoundConfig {
+            prevalent_wind: self.prevalent_wind(),
+            dealer: self.dealer(),
+            honba: self.honba,
+            riichi_sticks: self.riichi_sticks,
+            scores: self.scores.clone(),
+            rules: self.config.rules.clone(),
+        }
+    }
+
+    /// 次の局を始める
+    pub fn next_round(&mut self) -> Result<Round, failure::Error> {
+        if self.finished {
+            return Err(format_err!("対局は終了しています"));
+        }
+        let seed = self.rng.next_u64();
+        Round::new(self.round_config(), seed)
+    }
+
+    /// 終わった局の結果を反映して、親と本場を進める
+    ///
+    /// 親の和了(ダブロンで親を含む場合も)、親の聴牌流局、途中流局は連荘、流局は本場を積む。対局が終わった場合は
+    /// 残った供託をトップが受け取る
+    pub fn finish_round(&mut self, round: &Round) -> Result<(), failure::Error> {
+        let result = round.result().ok_or_else(|| format_err!("局が終わっていません"))?;
+        let dealer = self.dealer();
+        let (renchan, draw) = match result {
+            RoundResult::Win { wins, .. } => (wins.iter().any(|win| win.seat == dealer), false),
+            RoundResult::ExhaustiveDraw { tenpai, .. } => (tenpai[dealer], true),
+            RoundResult::AbortiveDraw(_) => (true, true),
+        };
+        self.scores = round.scores().clone();
+        self.riichi_sticks = round.riichi_sticks();
+        if renchan || draw {
+            self.honba += 1;
+        } else {
+            self.honba = 0;
+        }
+        if !renchan {
+            self.kyoku += 1;
+        }
+
+        self.finished = self.is_over();
+        if self.finished {
+            let top = self.standings()[0].seat;
+            self.scores[top] += (self.riichi_sticks * self.config.rules.riichi_deposit) as i32;
+            self.riichi_sticks = 0;
+        }
+        Ok(())
+    }
+
+    /// 終了条件 (飛び、予定の局の終了、西入後に返しに達した場合、北入の手前)
+    fn is_over(&self) -> bool {
+        if self.config.bust && self.scores.iter().any(|score| *score < 0) {
+            return true;
+        }
+        let scheduled = self.config.length.winds() * self.config.players;
+        if self.kyoku < scheduled {
+            return false;
+        }
+        let reached = self.scores.iter().any(|score| *score >= self.config.return_points);
+        reached || !self.config.west_extension || self.kyoku >= scheduled + self.config.players
+    }
+
+    /// 現在の持ち点による順位とポイント
+    pub fn standings(&self) -> Vec<Standing> {
+        settle(&self.scores, &self.config)
+    }
+
+    /// 順位表
+    pub fn ranking_table(&self) -> String {
+        let mut table = "順位  席  持ち点  ポイント\n".to_string();
+        for standing in self.standings() {
+            table.push_str(&format!("{:>4}  {:>2}  {:>6}  {:>+8.1}\n", standing.rank, standing.seat, standing.points, standing.score));
+        }
+        table
+    }
+}
+
+/// 持ち点から順位とポイントを決める
+///
+/// 同点の場合は起家に近い席を上位とする
+pub fn settle(scores: &[i32], config: &MatchConfig) -> Vec<Standing> {
+    let mut seats: Vec<Seat> = (0..scores.len()).collect();
+    seats.sort_by(|a, b| scores[*b].cmp(&scores[*a]).then(a.cmp(b)));
+    let oka = (config.return_points - config.starting_points) * scores.len() as i32;
+    seats.into_iter().enumerate().map(|(i, seat)| {
+        let points = scores[seat];
+        let mut score = (points - config.return_points) as f64 / 1000.0 + *config.uma.get(i).unwrap_or(&0) as f64;
+        if i == 0 {
+            score += oka as f64 / 1000.0;
+        }
+        Standing { rank: i + 1, seat, points, score }
+    }).collect()
+}
@@ -4,6 +4,7 @@ use crate::groups::Set::{Chow, Pung, Pair};
 pub use std::str::FromStr;
 use std::fmt::{Display, Formatter, Error, Debug};
 use crate::score::Fu;
+use crate::rules::Rules;
 
 /// 複数枚の牌に関する情報
 pub trait Tiles {
@@ -123,106 +124,134 @@ impl Tiles for Vec<Tile> {
 
 pub struct TilesNewType(pub Vec<Tile>);
 
-impl FromStr for TilesNewType {
-    type Err = failure::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl TilesNewType {
+    /// 赤五を区別して読み取る
+    ///
+    /// 赤五(`0m`など)は通常の5として並びに含め、別途その一覧を返す
+    pub fn from_str_with_red(s: &str) -> Result<(Self, Vec<Tile>), failure::Error> {
         let mut tiles = Vec::with_capacity(14);
+        let mut red_fives = Vec::new();
+        // 種類の指定を待っている数字(`123m`の`123`)
+        let mut nums: Vec<u8> = Vec::with_capacity(14);
 
-        // パース
-        let mut iter = s.chars().peekable();
+        for c in s.chars() {
+            if Tile::is_ignorable(c) {
+                continue;
+            }
+            if let Some(u) = c.to_digit(10) {
+                nums.push(u as u8);
+                continue;
+            }
 
-        while let Some(c) = iter.next() {
-            let tile =
-                match c.to_string().parse::<u8>() {
-                    Ok(u) => {
-                        if u == 0 {
-                            return Err(format_err!("数字が不正です: {} in {}",u,s));
-                        }
-                        match iter.next() {
-                            Some(c) => {
-                                if Tile::characters_markers().contains(&c) {
-                                    // 萬子
-                                    Tile::Character(u)
-                                } else if Tile::circles_markers().contains(&c) {
-                                    // 筒子
-                                    Tile::Circle(u)
-                                } else if Tile::bamboos_markers().contains(&c) {
-                                    // 索子
-                                    Tile::Bamboo(u)
-                                } else {
-                                    match c.to_string().parse::<u8>() {
-                                        // 数字
-                                        Ok(u2) => {
-                                            // 123m456p789sのような省略記法の場合
-                                            let mut nums = Vec::with_capacity(14);
-                                            nums.push(u);
-                                            nums.push(u2);
-                                            while let Some(c) = iter.next() {
-                                                let mut tiles_tmp: Vec<Tile> =
-                                                    if Tile::characters_markers().contains(&c) {
-                                                        // 萬子
-                                                        nums.iter().map(|u| Tile::Character(u.clone())).collect()
-                                                    } else if Tile::circles_markers().contains(&c) {
-                                                        // 筒子
-                                                        nums.iter().map(|u| Tile::Circle(u.clone())).collect()
-                                                    } else if Tile::bamboos_markers().contains(&c) {
-                                                        // 索子
-                                                        nums.iter().map(|u| Tile::Bamboo(u.clone())).collect()
-                                                    } else {
-                                                        match c.to_string().parse::<u8>() {
-                                                            // 数字の連続
-                                                            Ok(u) => {
-                                                                nums.push(u);
-                                                                continue;
-                                                            }
-                                                            // エラー: 全く関係のない文字
-                                                            Err(_) => {
-                                                                return Err(format_err!("不正な文字があります: {} in {}",c,s));
-                                                            }
-                                                        }
-                                                    };
-                                                tiles.append(&mut tiles_tmp);
-                                                break;
-                                            }
-                                            continue;
-                                        }
-                                        // エラー(`123s4W`など)
-                                        Err(_) => {
-                                            return Err(format_err!("不正な文字があります: {} in {}",c,s));
-                                        }
-                                    }
-                                }
-                            }
-                            None => {
-                                return Err(format_err!("入力に不足があります: {}",s));
-                            }
-                        }
+            let suit: Option<fn(u8) -> Tile> =
+                if Tile::characters_markers().contains(&c) {
+                    // 萬子
+                    Some(Tile::Character)
+                } else if Tile::circles_markers().contains(&c) {
+                    // 筒子
+                    Some(Tile::Circle)
+                } else if Tile::bamboos_markers().contains(&c) {
+                    // 索子
+                    Some(Tile::Bamboo)
+                } else {
+                    None
+                };
+
+            if let Some(suit) = suit {
+                if nums.is_empty() {
+                    return Err(format_err!("数字がありません: {} in {}", c, s));
+                }
+                for u in nums.drain(..) {
+                    if u == 0 {
+                        // 赤五
+                        red_fives.push(suit(5));
+                        tiles.push(suit(5));
+                    } else {
+                        tiles.push(suit(u));
                     }
-                    Err(_) => {
-                        if Tile::east_markers().contains(&c) {
-                            Wind::East.tile()
-                        } else if Tile::south_markers().contains(&c) {
-                            Wind::South.tile()
-                        } else if Tile::west_markers().contains(&c) {
-                            Wind::West.tile()
-                        } else if Tile::north_markers().contains(&c) {
-                            Wind::North.tile()
-                        } else if Tile::white_markers().contains(&c) {
-                            Dragon::White.tile()
-                        } else if Tile::green_markers().contains(&c) {
-                            Dragon::Green.tile()
-                        } else if Tile::red_markers().contains(&c) {
-                            Dragon::Red.tile()
-                        } else {
-                            return Err(format_err!("入力が不正です: {} in {}",c,s));
-                        }
+                }
+            } else if Tile::honours_markers().contains(&c) {
+                // 字牌(1z-7z)
+                if nums.is_empty() {
+                    return Err(format_err!("数字がありません: {} in {}", c, s));
+                }
+                for u in nums.drain(..) {
+                    match Tile::from_honour_number(u) {
+                        Some(tile) => tiles.push(tile),
+                        None => return Err(format_err!("数字が不正です: {}{} in {}", u, c, s)),
                     }
-                };
-            tiles.push(tile);
+                }
+            } else {
+                // エラー(`123s4W`など)
+                if !nums.is_empty() {
+                    return Err(format_err!("不正な文字があります: {} in {}", c, s));
+                }
+                match Tile::from_honour_marker(c).or_else(|| Tile::from_glyph(c)) {
+                    Some(tile) => tiles.push(tile),
+                    None if Tile::is_flower_glyph(c) => {
+                        return Err(format_err!("花牌は採用されていません (花牌ありのルールでのみ指定できます): {} in {}", c, s));
+                    }
+                    None if c == Tile::joker_glyph() => {
+                        return Err(format_err!("ジョーカーは指定できません: {} in {}", c, s));
+                    }
+                    None if c == Tile::back_glyph() => {
+                        return Err(format_err!("裏向きの牌は暗槓の中でのみ指定できます: {} in {}", c, s));
+                    }
+                    None => return Err(format_err!("入力が不正です: {} in {}", c, s)),
+                }
+            }
+        }
+
+        if !nums.is_empty() {
+            return Err(format_err!("入力に不足があります: {}", s));
+        }
+
+        Ok((TilesNewType(tiles), red_fives))
+    }
+
+    /// MPSZ表記(`123m406p789s11z`)で出力する
+    ///
+    /// `red_fives`に含まれる5は赤五(`0`)として出力し、出力した分を取り除く
+    pub fn to_mpsz(&self, red_fives: &mut Vec<Tile>) -> String {
+        Self::write_mpsz(&self.0, red_fives, None)
+    }
+
+    /// MPSZ表記で出力する (鳴いた牌の直後には出所の記号を置く)
+    fn write_mpsz(tiles: &[Tile], red_fives: &mut Vec<Tile>, call: Option<&Call>) -> String {
+        let mut string = String::new();
+        let mut call = call;
+        let mut iter = tiles.iter().peekable();
+        while let Some(tile) = iter.next() {
+            let (u, suit) = tile.mpsz_parts();
+            match red_fives.iter().position(|red| red == tile) {
+                Some(i) => {
+                    red_fives.remove(i);
+                    string.push('0');
+                }
+                None => string.push_str(&u.to_string()),
+            }
+            if let Some(called) = call {
+                if &called.tile == tile {
+                    string.push(called.source.marker());
+                    call = None;
+                }
+            }
+            // 種類が変わる所で種類を表す文字を置く
+            match iter.peek() {
+                Some(next) if next.mpsz_parts().1 == suit => {}
+                _ => string.push(suit),
+            }
         }
+        string
+    }
+}
+
+impl FromStr for TilesNewType {
+    type Err = failure::Error;
 
-        Ok(TilesNewType(tiles))
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (tiles, _) = TilesNewType::from_str_with_red(s)?;
+        Ok(tiles)
     }
 }
 
@@ -251,8 +280,11 @@ impl<T: Display + Debug> Display for DisplayVec<T> {
     }
 }
 
+/// 当たり牌を含まない手牌 (手牌, 晒した面子, 赤五)
+pub type PartialHand = (Vec<Tile>, Vec<OpenSet>, Vec<Tile>);
+
 /// 手牌という概念
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Hand {
     /// 手牌(晒していない手牌)
     pub tiles: Vec<Tile>,
@@ -260,31 +292,88 @@ pub struct Hand {
     pub open_sets: Vec<OpenSet>,
     /// 当たり牌
     pub winning: Tile,
+    /// ツモ和了か否か(譜面で指定された場合のみ)
+    pub draw: Option<bool>,
+    /// 赤五(晒した牌を含む。通常の5として`tiles`等にも含まれる)
+    pub red_fives: Vec<Tile>,
+    /// 抜いた花牌・季節牌の枚数 (花牌ありのルールの場合のみ)
+    pub flowers: u32,
 }
 
 impl Hand {
     pub fn tiles(&self) -> &Vec<Tile> {
         &self.tiles
     }
-}
 
-impl FromStr for Hand {
-    type Err = failure::Error;
+    pub fn tsumo_markers() -> Vec<&'static str> {
+        vec!["tsumo", "ツモ", "自摸"]
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // 手牌
+    pub fn ron_markers() -> Vec<&'static str> {
+        vec!["ron", "ロン", "栄和"]
+    }
+
+    /// `Hand::from_str`で読み取れるMPSZ表記で出力する (例: `123m406p789s1z[5^55p]+1z tsumo`)
+    ///
+    /// 赤五は手牌、晒した面子、当たり牌の順に割り当てる
+    pub fn to_mpsz(&self) -> String {
+        let mut red_fives = self.red_fives.clone();
+        let mut tiles = self.tiles.clone();
+        if let Some(i) = tiles.iter().position(|tile| tile == &self.winning) {
+            tiles.remove(i);
+        }
+
+        let mut string = TilesNewType(tiles).to_mpsz(&mut red_fives);
+        self.open_sets.iter().for_each(|open| string.push_str(&open.to_mpsz(&mut red_fives)));
+        (0..self.flowers as usize).for_each(|i| string.push(Tile::flower_glyph(i)));
+        string.push('+');
+        string.push_str(&TilesNewType(vec![self.winning.clone()]).to_mpsz(&mut red_fives));
+        match self.draw {
+            Some(true) => string.push_str(" tsumo"),
+            Some(false) => string.push_str(" ron"),
+            None => {}
+        }
+        string
+    }
+
+    /// ルールに従って手牌を読み取る
+    ///
+    /// 花牌・季節牌の絵文字は花牌ありのルールでのみ受け付ける
+    pub fn from_str_with_rules(s: &str, rules: &Rules) -> Result<Self, failure::Error> {
+        let hand = Hand::from_str(s)?;
+        if hand.flowers > 0 && !rules.flowers {
+            return Err(format_err!("花牌なしのルールです: {}", s));
+        }
+        Ok(hand)
+    }
+
+    /// 花牌・季節牌の絵文字を抜き出し、その枚数と残りの譜面を返す (各1枚、最大8枚)
+    fn split_flowers(s: &str) -> Result<(u32, String), failure::Error> {
+        let flowers: Vec<char> = s.chars().filter(|c| Tile::is_flower_glyph(*c)).collect();
+        if flowers.iter().enumerate().any(|(i, c)| flowers[..i].contains(c)) {
+            return Err(format_err!("同じ花牌が複数あります: {}", s));
+        }
+        Ok((flowers.len() as u32, s.chars().filter(|c| !Tile::is_flower_glyph(*c)).collect()))
+    }
+
+    /// 当たり牌を含まない手牌(13枚の手牌や打牌前の14枚など)を読み取る
+    ///
+    /// 手牌(記述順)、晒した面子、赤五を返す。枚数は検証しない
+    pub fn parse_tiles(body: &str) -> Result<PartialHand, failure::Error> {
         let mut tiles = Vec::with_capacity(14);
         let mut open_sets = Vec::with_capacity(4);
+        let mut red_fives = Vec::new();
 
-        // パース
-        let mut iter = s.chars().peekable();
+        let body: String = body.chars().filter(|c| !Tile::is_ignorable(*c)).collect();
+        let mut iter = body.chars().peekable();
         let mut store_tmp = String::new();
         while let Some(c) = iter.next() {
             if c == '[' {
                 // これまでの並びを登録
                 if store_tmp.len() != 0 {
-                    let TilesNewType(mut tiles_tmp) = TilesNewType::from_str(&store_tmp)?;
+                    let (TilesNewType(mut tiles_tmp), mut red_tmp) = TilesNewType::from_str_with_red(&store_tmp)?;
                     tiles.append(&mut tiles_tmp);
+                    red_fives.append(&mut red_tmp);
                 }
                 // 鳴き成立の面子の譜面を読み取る
                 let mut chars = Vec::new();
@@ -294,14 +383,17 @@ impl FromStr for Hand {
                 }
                 store_tmp = chars.iter().collect();
                 // 登録
-                open_sets.push(OpenSet::from_str(&store_tmp)?);
+                let (open_set, mut red_tmp) = OpenSet::from_str_with_red(&store_tmp)?;
+                open_sets.push(open_set);
+                red_fives.append(&mut red_tmp);
                 // 一時変数を初期化
                 store_tmp = String::new();
             } else if c == '(' {
                 // これまでの並びを登録
                 if store_tmp.len() != 0 {
-                    let TilesNewType(mut tiles_tmp) = TilesNewType::from_str(&store_tmp)?;
+                    let (TilesNewType(mut tiles_tmp), mut red_tmp) = TilesNewType::from_str_with_red(&store_tmp)?;
                     tiles.append(&mut tiles_tmp);
+                    red_fives.append(&mut red_tmp);
                 }
                 // 鳴き成立の面子の譜面を読み取る
                 let mut chars = Vec::new();
@@ -309,10 +401,22 @@ impl FromStr for Hand {
                     if c == ')' { break; }
                     chars.push(c);
                 }
-                store_tmp = chars.iter().collect();
-                let TilesNewType(tiles_tmp) = TilesNewType::from_str(&store_tmp)?;
+                // 裏向きの牌(🀫)は見えている牌と同じ牌とみなす
+                let backs = chars.iter().filter(|c| **c == Tile::back_glyph()).count();
+                store_tmp = chars.iter().filter(|c| **c != Tile::back_glyph()).collect();
+                let (TilesNewType(mut tiles_tmp), mut red_tmp) = TilesNewType::from_str_with_red(&store_tmp)?;
+                if backs > 0 {
+                    match tiles_tmp.first().cloned() {
+                        Some(tile) if tiles_tmp.is_flat() => {
+                            (0..backs).for_each(|_| tiles_tmp.push(tile.clone()));
+                        }
+                        _ => return Err(format_err!("暗槓が不正です: ({})", chars.iter().collect::<String>())),
+                    }
+                }
+                tiles_tmp.sort();
                 // 登録
                 open_sets.push(OpenSet::ConcealedKong(tiles_tmp));
+                red_fives.append(&mut red_tmp);
                 // 一時変数を初期化
                 store_tmp = String::new();
             } else {
@@ -320,8 +424,83 @@ impl FromStr for Hand {
             }
         }
         if store_tmp.len() != 0 {
-            let TilesNewType(mut tiles_tmp) = TilesNewType::from_str(&store_tmp)?;
+            let (TilesNewType(mut tiles_tmp), mut red_tmp) = TilesNewType::from_str_with_red(&store_tmp)?;
             tiles.append(&mut tiles_tmp);
+            red_fives.append(&mut red_tmp);
+        }
+
+        Ok((tiles, open_sets, red_fives))
+    }
+
+    /// 当たり牌の区切り(`+`または`,`)で譜面を分割する
+    ///
+    /// 括弧の中の`+`(加槓)は区切りとみなさない
+    fn split_winning(s: &str) -> (&str, Option<&str>) {
+        let mut depth = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                '[' | '(' => depth += 1,
+                ']' | ')' => depth -= 1,
+                '+' | ',' if depth == 0 => return (&s[..i], Some(&s[i + c.len_utf8()..])),
+                _ => {}
+            }
+        }
+        (s, None)
+    }
+
+    /// 当たり牌の指定(`7s`, `7s tsumo`, `7sロン`など)を読み取る
+    fn parse_winning(s: &str) -> Result<(Tile, Option<bool>, bool), failure::Error> {
+        let mut body = s.trim();
+        let mut draw = None;
+        for (markers, value) in [(Hand::tsumo_markers(), true), (Hand::ron_markers(), false)] {
+            for marker in markers {
+                if body.to_ascii_lowercase().ends_with(marker) {
+                    body = body[..body.len() - marker.len()].trim_end();
+                    draw = Some(value);
+                }
+            }
+        }
+        let body: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+        let (TilesNewType(tiles), red_fives) = TilesNewType::from_str_with_red(&body)?;
+        match tiles.as_slice() {
+            [winning] => Ok((winning.clone(), draw, !red_fives.is_empty())),
+            _ => Err(format_err!("当たり牌の指定が不正です: {}", s)),
+        }
+    }
+}
+
+impl FromStr for Hand {
+    type Err = failure::Error;
+
+    /// 手牌を読み取る
+    ///
+    /// `+`または`,`の後に当たり牌とツモ/ロンを指定できる (例: `123456778899s東東,7s tsumo`)。
+    /// 指定がない場合は最後の牌を当たり牌とする。花牌・季節牌の絵文字は抜いた牌として数える
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (flowers, notation) = Hand::split_flowers(s)?;
+        // 当たり牌の指定
+        let (body, winning) = Hand::split_winning(&notation);
+        let (winning, draw, red_winning) = match winning {
+            Some(winning) => {
+                let (tile, draw, red) = Hand::parse_winning(winning)?;
+                (Some(tile), draw, red)
+            }
+            None => (None, None, false),
+        };
+
+        // パース
+        let (mut tiles, open_sets, mut red_fives) = Hand::parse_tiles(body)?;
+
+        // 当たり牌が手牌に含まれていない場合は加える
+        if let Some(winning) = &winning {
+            if tiles.len() + 3 * open_sets.len() == 13 {
+                tiles.push(winning.clone());
+                if red_winning {
+                    red_fives.push(winning.clone());
+                }
+            } else if !tiles.contains(winning) {
+                return Err(format_err!("当たり牌が手牌にありません: {}",s));
+            }
         }
 
         // 少牌or多牌
@@ -332,22 +511,29 @@ impl FromStr for Hand {
         }
 
         // 当たり牌
-        let winning = tiles.last().unwrap().clone();
+        let winning = match winning {
+            Some(winning) => winning,
+            None => tiles.last().unwrap().clone(),
+        };
 
         // ソート
         tiles.sort();
+        red_fives.sort();
 
-        println!("{}", &Hand { tiles: tiles.clone(), open_sets: open_sets.clone(), winning: winning.clone() });
-
-        Ok(Hand { tiles, open_sets, winning })
+        Ok(Hand { tiles, open_sets, winning, draw, red_fives, flowers })
     }
 }
 
+/// `{}`は`Hand::from_str`で読み取れるMPSZ表記、`{:#}`は牌の絵文字で出力する
 impl Display for Hand {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if !f.alternate() {
+            return write!(f, "{}", self.to_mpsz());
+        }
         TilesNewType(self.tiles.clone()).fmt(f)?;
         write!(f, " <{}> ", &self.winning)?;
-        self.open_sets.iter().try_for_each(|set| std::fmt::Display::fmt(set, f))
+        self.open_sets.iter().try_for_each(|set| std::fmt::Display::fmt(set, f))?;
+        (0..self.flowers as usize).try_for_each(|i| write!(f, "{}", Tile::flower_glyph(i)))
     }
 }
 
@@ -372,46 +558,186 @@ pub trait Sets {
     fn vec(&self) -> Vec<Tile>;
 }
 
+/// 鳴いた相手
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// 上家
+    Left,
+    /// 対面
+    Opposite,
+    /// 下家
+    Right,
+}
+
+impl Source {
+    /// 譜面上の記号 (`<`: 上家, `^`: 対面, `>`: 下家)
+    pub fn marker(&self) -> char {
+        match &self {
+            Source::Left => '<',
+            Source::Opposite => '^',
+            Source::Right => '>',
+        }
+    }
+
+    pub fn from_marker(c: char) -> Option<Source> {
+        match c {
+            '<' => Some(Source::Left),
+            '^' => Some(Source::Opposite),
+            '>' => Some(Source::Right),
+            _ => None,
+        }
+    }
+}
+
+/// 鳴いた牌とその出所
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// 鳴いた牌
+    pub tile: Tile,
+    /// 鳴いた相手
+    pub source: Source,
+}
+
+impl Call {
+    pub fn new(tile: Tile, source: Source) -> Self {
+        Call { tile, source }
+    }
+}
+
 /// 鳴きで成立した面子
+///
+/// 鳴いた牌と相手が分かる場合は`Call`を持つ
 #[derive(Debug, Clone, PartialEq)]
 pub enum OpenSet {
     /// ポン
-    Pung(Vec<Tile>),
+    Pung(Vec<Tile>, Option<Call>),
     /// チー
-    Chow(Vec<Tile>),
+    Chow(Vec<Tile>, Option<Call>),
     /// 明槓
-    Kong(Vec<Tile>),
+    Kong(Vec<Tile>, Option<Call>),
+    /// 加槓 (`Call`はポンした時のもの)
+    AddedKong(Vec<Tile>, Option<Call>),
     /// 暗槓
     ConcealedKong(Vec<Tile>),
 }
 
+impl OpenSet {
+    /// 鳴いた牌とその出所
+    pub fn call(&self) -> Option<&Call> {
+        match &self {
+            OpenSet::Pung(_, call) => call.as_ref(),
+            OpenSet::Chow(_, call) => call.as_ref(),
+            OpenSet::Kong(_, call) => call.as_ref(),
+            OpenSet::AddedKong(_, call) => call.as_ref(),
+            OpenSet::ConcealedKong(_) => None,
+        }
+    }
+
+    /// MPSZ表記で出力する (例: `[2<13s]`, `[+5^555p]`, `(5555m)`)
+    pub fn to_mpsz(&self, red_fives: &mut Vec<Tile>) -> String {
+        match &self {
+            OpenSet::ConcealedKong(tiles) => {
+                format!("({})", TilesNewType::write_mpsz(tiles, red_fives, None))
+            }
+            OpenSet::AddedKong(tiles, call) => {
+                format!("[+{}]", TilesNewType::write_mpsz(tiles, red_fives, call.as_ref()))
+            }
+            OpenSet::Pung(tiles, call) | OpenSet::Chow(tiles, call) | OpenSet::Kong(tiles, call) => {
+                format!("[{}]", TilesNewType::write_mpsz(tiles, red_fives, call.as_ref()))
+            }
+        }
+    }
+
+    /// 槓子か否か
+    pub fn is_kong(&self) -> bool {
+        matches!(self, OpenSet::Kong(..) | OpenSet::AddedKong(..) | OpenSet::ConcealedKong(_))
+    }
+}
+
 impl FromStr for OpenSet {
     type Err = failure::Error;
 
+    /// `[123s]`の中身を読み取る
+    ///
+    /// 鳴いた牌の直後に出所の記号(`<`, `^`, `>`)を置くことができる (例: `2<13s`, `5^55p`)。
+    /// 先頭の`+`は加槓を表す (例: `+5<555p`)
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (open_set, _) = OpenSet::from_str_with_red(s)?;
+        Ok(open_set)
+    }
+}
+
+impl OpenSet {
+    /// 赤五を区別して読み取る (`FromStr`を参照)
+    pub fn from_str_with_red(s: &str) -> Result<(Self, Vec<Tile>), failure::Error> {
+        // 加槓
+        let (added, body) = match s.strip_prefix('+') {
+            Some(body) => (true, body),
+            None => (false, s),
+        };
+
+        // 出所の記号を取り除き、鳴いた牌の位置を記録する
+        let mut plain = String::new();
+        let mut called: Option<(usize, Source)> = None;
+        let mut count = 0;
+        for c in body.chars() {
+            if Tile::is_ignorable(c) {
+                continue;
+            }
+            match Source::from_marker(c) {
+                Some(source) => {
+                    if count == 0 || called.is_some() {
+                        return Err(format_err!("鳴いた牌の指定が不正です: [{}]", s));
+                    }
+                    called = Some((count - 1, source));
+                }
+                None => {
+                    if !Tile::is_suit_marker(c) {
+                        count += 1;
+                    }
+                    plain.push(c);
+                }
+            }
+        }
+
         // 中身
-        let TilesNewType(mut vec) = TilesNewType::from_str(&s)?;
+        let (TilesNewType(mut vec), red_fives) = TilesNewType::from_str_with_red(&plain)?;
+        let call = match called {
+            Some((index, source)) => match vec.get(index) {
+                Some(tile) => Some(Call::new(tile.clone(), source)),
+                None => return Err(format_err!("鳴いた牌の指定が不正です: [{}]", s)),
+            },
+            None => None,
+        };
         vec.sort();
 
         // validation
         match vec.len() {
-            3 => {
+            3 if !added => {
                 if vec.is_flat() {
                     // 刻子
-                    Ok(OpenSet::Pung(vec))
+                    Ok((OpenSet::Pung(vec, call), red_fives))
                 } else if vec.is_sequential() {
-                    // 順子
-                    Ok(OpenSet::Chow(vec))
+                    // 順子 (上家からしか鳴けない)
+                    match &call {
+                        Some(Call { source: Source::Opposite, .. }) | Some(Call { source: Source::Right, .. }) => {
+                            Err(format_err!("チーは上家からしかできません: [{}]", s))
+                        }
+                        _ => Ok((OpenSet::Chow(vec, call), red_fives)),
+                    }
                 } else {
                     Err(format_err!("入力が不正です: [{}]", s))
                 }
             }
             4 => {
-                if vec.is_flat() {
-                    // 明槓 (暗槓はHand.parse()時に判断する)
-                    Ok(OpenSet::Kong(vec))
-                } else {
+                if !vec.is_flat() {
                     Err(format_err!("入力が不正です: [{}]", s))
+                } else if added {
+                    // 加槓
+                    Ok((OpenSet::AddedKong(vec, call), red_fives))
+                } else {
+                    // 明槓 (暗槓はHand.parse()時に判断する)
+                    Ok((OpenSet::Kong(vec, call), red_fives))
                 }
             }
             _ => {
@@ -421,24 +747,33 @@ impl FromStr for OpenSet {
     }
 }
 
+/// `{}`はMPSZ表記、`{:#}`は牌の絵文字で出力する
 impl Display for OpenSet {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if !f.alternate() {
+            return write!(f, "{}", self.to_mpsz(&mut Vec::new()));
+        }
         match &self {
-            OpenSet::Pung(tiles) => {
+            OpenSet::Pung(tiles, _) => {
                 write!(f, "[")?;
                 TilesNewType(tiles.clone()).fmt(f)?;
                 write!(f, "]")
             }
-            OpenSet::Chow(tiles) => {
+            OpenSet::Chow(tiles, _) => {
                 write!(f, "[")?;
                 TilesNewType(tiles.clone()).fmt(f)?;
                 write!(f, "]")
             }
-            OpenSet::Kong(tiles) => {
+            OpenSet::Kong(tiles, _) => {
                 write!(f, "[")?;
                 TilesNewType(tiles.clone()).fmt(f)?;
                 write!(f, "]")
             }
+            OpenSet::AddedKong(tiles, _) => {
+                write!(f, "[+")?;
+                TilesNewType(tiles.clone()).fmt(f)?;
+                write!(f, "]")
+            }
             OpenSet::ConcealedKong(tiles) => {
                 write!(f, "(")?;
                 TilesNewType(tiles.clone()).fmt(f)?;
@@ -451,7 +786,7 @@ impl Display for OpenSet {
 impl Sets for OpenSet {
     fn fu(&self) -> Fu {
         match &self {
-            OpenSet::Pung(vec) => {
+            OpenSet::Pung(vec, _) => {
                 Fu(if vec.all_yaotyu() {
                     4
                 } else { 2 })
@@ -461,7 +796,7 @@ impl Sets for OpenSet {
                     32
                 } else { 16 })
             }
-            OpenSet::Kong(vec) => {
+            OpenSet::Kong(vec, _) | OpenSet::AddedKong(vec, _) => {
                 Fu(if vec.all_yaotyu() {
                     16
                 } else { 8 })
@@ -472,9 +807,10 @@ impl Sets for OpenSet {
 
     fn all_character(&self) -> bool {
         let vec = match &self {
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         };
         vec.iter().all(|tile| match tile {
@@ -485,9 +821,10 @@ impl Sets for OpenSet {
 
     fn all_circle(&self) -> bool {
         let vec = match &self {
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         };
         vec.iter().all(|tile| match tile {
@@ -498,9 +835,10 @@ impl Sets for OpenSet {
 
     fn all_bamboo(&self) -> bool {
         let vec = match &self {
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         };
         vec.iter().all(|tile| match tile {
@@ -511,9 +849,10 @@ impl Sets for OpenSet {
 
     fn all_honor(&self) -> bool {
         let vec = match &self {
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         };
         vec.iter().all(|tile| match tile {
@@ -524,9 +863,10 @@ impl Sets for OpenSet {
 
     fn vec(&self) -> Vec<Tile> {
         match &self {
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.clone()
     }
@@ -535,36 +875,40 @@ impl Sets for OpenSet {
 impl Tiles for OpenSet {
     fn contains_yaotyu(&self) -> bool {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.contains_yaotyu()
     }
 
     fn all_yaotyu(&self) -> bool {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.all_yaotyu()
     }
 
     fn contains_terminal(&self) -> bool {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.contains_terminal()
     }
 
     fn all_terminal(&self) -> bool {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.all_terminal()
     }
@@ -572,36 +916,39 @@ impl Tiles for OpenSet {
 
     fn is_sequential(&self) -> bool {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.is_sequential()
     }
 
     fn is_flat(&self) -> bool {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.is_flat()
     }
 
     fn count(&self, tile: &Tile) -> u8 {
         match &self {
-            OpenSet::Pung(vec) => vec,
-            OpenSet::Chow(vec) => vec,
-            OpenSet::Kong(vec) => vec,
+            OpenSet::Pung(vec, _) => vec,
+            OpenSet::Chow(vec, _) => vec,
+            OpenSet::Kong(vec, _) => vec,
+            OpenSet::AddedKong(vec, _) => vec,
             OpenSet::ConcealedKong(vec) => vec,
         }.count(tile)
     }
 
     fn sum_tile(&self) -> Option<Tile> {
         match &self {
-            OpenSet::Pung(vec) => vec.clone(),
-            OpenSet::Chow(vec) => vec.clone(),
-            OpenSet::Kong(vec) => {
+            OpenSet::Pung(vec, _) => vec.clone(),
+            OpenSet::Chow(vec, _) => vec.clone(),
+            OpenSet::Kong(vec, _) | OpenSet::AddedKong(vec, _) => {
                 let mut vec = vec.clone();
                 vec.remove(0);
                 vec
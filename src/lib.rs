@@ -1,13 +1,31 @@
 #[macro_use]
 extern crate failure;
 
-mod tiles;
-mod parse;
-mod groups;
-mod yaku;
-mod evaluate;
-mod score;
+pub mod tiles;
+pub mod parse;
+pub mod groups;
+pub mod yaku;
+pub mod evaluate;
+pub mod score;
+pub mod shanten;
+pub mod rng;
+pub mod wall;
+pub mod payment;
+pub mod round;
+pub mod calls;
+pub mod game;
+pub mod agent;
+pub mod simulator;
+pub mod record;
+pub mod rules;
+pub mod table;
+pub mod output;
+mod face;
+pub mod svg;
+pub mod terminal;
 pub mod calculator;
+#[cfg(feature = "serde")]
+pub mod serialize;
 
 pub use calculator::calc;
 
@@ -15,6 +33,7 @@ pub use calculator::calc;
 mod tests {
     use crate::tiles::{Tile, Dragon, Wind};
     use crate::groups::*;
+    use crate::parse::ParsedHand;
 
     #[test]
     fn sort_tiles() {
@@ -95,4 +114,1180 @@ mod tests {
         assert_eq!(hand.tiles(), &vec);
         Ok(())
     }
+
+    #[test]
+    fn best_interpretation() -> Result<(), failure::Error> {
+        use crate::evaluate::Evaluator;
+
+        let evaluator = Evaluator::new(Some(Wind::East.tile()), Some(Wind::East.tile()), Vec::new(), Vec::new());
+        let all = evaluator.evaluate_all_str("222333444555m66p", false, &Vec::new())?;
+        // 安い順に並び、同じ入力に対して順序が変わらない
+        assert!(all.windows(2).all(|pair| pair[0].cmp_by(&pair[1], true, false) != std::cmp::Ordering::Greater));
+        let again = evaluator.evaluate_all_str("222333444555m66p", false, &Vec::new())?;
+        assert_eq!(all.iter().map(|e| e.node().to_string()).collect::<Vec<_>>(),
+                   again.iter().map(|e| e.node().to_string()).collect::<Vec<_>>());
+
+        let best = evaluator.evaluate_str("111222333m456p77s", false, &Vec::new())?.unwrap();
+        assert_eq!(best.yaku_list(), &vec!["三暗刻 / Three closed triplets".to_string()]);
+        assert_eq!(best.score().points(true, false), 4800);
+        Ok(())
+    }
+
+    #[test]
+    fn open_set_call() -> Result<(), failure::Error> {
+        let chow = OpenSet::from_str("2<13s")?;
+        assert_eq!(chow, OpenSet::Chow(vec![Tile::Bamboo(1), Tile::Bamboo(2), Tile::Bamboo(3)],
+                                       Some(Call::new(Tile::Bamboo(2), Source::Left))));
+        let added = OpenSet::from_str("+5^555p")?;
+        assert_eq!(added.call(), Some(&Call::new(Tile::Circle(5), Source::Opposite)));
+        assert!(added.is_kong());
+        assert!(OpenSet::from_str("1>23s").is_err());
+        assert!(OpenSet::from_str("+555p").is_err());
+
+        let hand = Hand::from_str("234m234p[2<34s][+東^東東東]8p8p")?;
+        assert_eq!(hand.open_sets.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_winning() -> Result<(), failure::Error> {
+        // 当たり牌が手牌の途中にある場合
+        let hand = Hand::from_str("123456778899s東東,7s")?;
+        assert_eq!(hand.winning, Tile::Bamboo(7));
+        assert_eq!(hand.draw, None);
+
+        // 手牌11枚とチー + 当たり牌
+        let hand = Hand::from_str("123m456p789s東東 [2<34s] + 7s tsumo")?;
+        assert_eq!(hand.winning, Tile::Bamboo(7));
+        assert_eq!(hand.tiles().len(), 11);
+        assert_eq!(hand.draw, Some(true));
+
+        let hand = Hand::from_str("123m456p789s東東[+5^555p]+東ロン")?;
+        assert_eq!(hand.winning, Wind::East.tile());
+        assert_eq!(hand.draw, Some(false));
+
+        assert!(Hand::from_str("123456778899s東東,1m").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn mpsz() -> Result<(), failure::Error> {
+        let TilesNewType(tiles) = TilesNewType::from_str("1234567z")?;
+        assert_eq!(tiles, vec![Wind::East.tile(), Wind::South.tile(), Wind::West.tile(), Wind::North.tile(),
+                               Dragon::White.tile(), Dragon::Green.tile(), Dragon::Red.tile()]);
+        assert!(TilesNewType::from_str("8z").is_err());
+        assert!(TilesNewType::from_str("123").is_err());
+
+        let hand = Hand::from_str("406m123p789s[0<55s]11z+1z")?;
+        assert_eq!(hand.red_fives, vec![Tile::Character(5), Tile::Bamboo(5)]);
+        assert!(hand.tiles().contains(&Tile::Character(5)));
+
+        for notation in &["406m123p789s1z[0<55s]+1z", "12345678899s11z+7s tsumo", "789s1116z(5555m)[1<23p]+6z ron"] {
+            let hand = Hand::from_str(notation)?;
+            assert_eq!(&hand.to_mpsz(), notation);
+            let parsed = Hand::from_str(&hand.to_mpsz())?;
+            assert_eq!(parsed.tiles, hand.tiles);
+            assert_eq!(parsed.open_sets, hand.open_sets);
+            assert_eq!(parsed.winning, hand.winning);
+            assert_eq!(parsed.red_fives, hand.red_fives);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn thirteen_orphans_node() -> Result<(), failure::Error> {
+        // 雀頭の候補が1つしかない通常の手に国士の解釈を作らない
+        let hand = Hand::from_str("123m456p789s123s11z")?;
+        let nodes = ParsedHand::new(&hand).nodes;
+        assert!(nodes.iter().all(|node| node.sets.iter().all(|set| set.vec().len() <= 4)));
+
+        let hand = Hand::from_str("19m19p19s12345677z")?;
+        assert!(ParsedHand::new(&hand).nodes.iter().any(|node| node.sets.len() == 2));
+        Ok(())
+    }
+
+    /// 4面子1雀頭の手牌 (花牌を抜いている場合もある) をランダムに生成する
+    fn generate_hand(rng: &mut crate::rng::Rng) -> Hand {
+        let kinds: Vec<Tile> = (1..=9).map(Tile::Character)
+            .chain((1..=9).map(Tile::Circle))
+            .chain((1..=9).map(Tile::Bamboo))
+            .chain((1..=7).map(|u| Tile::from_honour_number(u).unwrap()))
+            .collect();
+        let sources = [Source::Left, Source::Opposite, Source::Right];
+
+        loop {
+            let mut tiles = Vec::new();
+            let mut open_sets = Vec::new();
+            let mut used: Vec<Tile> = Vec::new();
+            for _ in 0..4 {
+                let first = kinds[rng.below(kinds.len())].clone();
+                let chow = first.next().and_then(|second| second.next().map(|third| vec![first.clone(), second, third]));
+                let mut group = match chow {
+                    Some(chow) if rng.below(2) == 0 => chow,
+                    _ => vec![first.clone(); 3],
+                };
+                let open = rng.below(3);
+                if group.is_flat() && open == 0 {
+                    group.push(first.clone());
+                }
+                used.append(&mut group.clone());
+                if open == 2 {
+                    tiles.append(&mut group);
+                    continue;
+                }
+                let call = Call::new(group[rng.below(group.len())].clone(), sources[rng.below(3)]);
+                open_sets.push(match (group.len(), group.is_flat()) {
+                    (4, _) => match rng.below(3) {
+                        0 => OpenSet::ConcealedKong(group),
+                        1 => OpenSet::AddedKong(group, Some(call)),
+                        _ => OpenSet::Kong(group, Some(call)),
+                    },
+                    (_, true) => OpenSet::Pung(group, Some(call)),
+                    _ => OpenSet::Chow(group, Some(Call::new(call.tile, Source::Left))),
+                });
+            }
+            let head = kinds[rng.below(kinds.len())].clone();
+            used.append(&mut vec![head.clone(); 2]);
+            tiles.append(&mut vec![head; 2]);
+            if kinds.iter().any(|kind| used.count(kind) > 4) {
+                continue;
+            }
+
+            tiles.sort();
+            let winning = tiles[rng.below(tiles.len())].clone();
+            let mut red_fives: Vec<Tile> = vec![Tile::Character(5), Tile::Circle(5), Tile::Bamboo(5)].into_iter()
+                .filter(|five| used.contains(five) && rng.below(2) == 0)
+                .collect();
+            red_fives.sort();
+            let draw = match rng.below(3) {
+                0 => None,
+                1 => Some(true),
+                _ => Some(false),
+            };
+            let flowers = if rng.below(4) == 0 { rng.below(8) as u32 + 1 } else { 0 };
+            return Hand { tiles, open_sets, winning, draw, red_fives, flowers };
+        }
+    }
+
+    #[test]
+    fn format_round_trip() -> Result<(), failure::Error> {
+        let mut rng = crate::rng::Rng::new(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let hand = generate_hand(&mut rng);
+            let formatted = hand.to_string();
+            assert_eq!(Hand::from_str(&formatted)?, hand, "{}", formatted);
+
+            for open_set in &hand.open_sets {
+                let formatted = open_set.to_string();
+                let inner = &formatted[1..formatted.len() - 1];
+                if let OpenSet::ConcealedKong(_) = open_set { continue; }
+                assert_eq!(&OpenSet::from_str(inner)?, open_set, "{}", formatted);
+            }
+
+            for node in ParsedHand::new(&hand).nodes {
+                let reparsed = Hand::from_str(&node.to_string())?;
+                assert!(ParsedHand::new(&reparsed).nodes.contains(&node), "{}", node);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn glyphs() -> Result<(), failure::Error> {
+        use crate::evaluate::Evaluator;
+        use crate::rules::Rules;
+        use crate::score::Han;
+        use crate::yaku::situation::SituationYaku;
+
+        let hand = Hand::from_str("🀇🀈🀉🀙🀚🀛🀐🀑🀒🀀🀀🀀🀄\u{FE0F}🀄")?;
+        assert_eq!(hand, Hand::from_str("123m123p123s111z77z")?);
+
+        let hand = Hand::from_str("🀇🀈🀉🀙🀚🀛🀀🀀🀀🀄🀄(🀫🀐🀐🀫)")?;
+        assert_eq!(hand.open_sets, vec![OpenSet::ConcealedKong(vec![Tile::Bamboo(1); 4])]);
+
+        // 絵文字での出力を読み戻せる
+        let TilesNewType(tiles) = TilesNewType::from_str("19m19p19s1234567z")?;
+        let glyphs = TilesNewType(tiles.clone()).to_string();
+        let TilesNewType(parsed) = TilesNewType::from_str(&glyphs)?;
+        assert_eq!(parsed, tiles);
+
+        assert!(TilesNewType::from_str("🀫").is_err());
+
+        // ジョーカーは専用のエラーにする
+        let error = TilesNewType::from_str("123m🀪").err().unwrap().to_string();
+        assert!(error.starts_with("ジョーカー"), "{}", error);
+
+        // 花牌・季節牌は抜いた牌として読み取り、花牌ありのルールでのみ1枚1翻のドラとする
+        let notation = "123m456p789s123s1z🀢🀦+1z";
+        assert_eq!(Hand::from_str(notation)?.flowers, 2);
+        assert!(Hand::from_str_with_rules(notation, &Rules::standard()).is_err());
+        assert!(Hand::from_str("123m456p789s123s1z🀢🀢+1z").is_err());
+        assert_eq!(Hand::from_str("123m456p789s123s1z🀢🀣🀤🀥🀦🀧🀨🀩+1z")?.flowers, 8);
+        let rules = Rules { flowers: true, ..Rules::standard() };
+        let hand = Hand::from_str_with_rules(notation, &rules)?;
+        assert_eq!(hand.flowers, 2);
+        assert_eq!(Hand::from_str_with_rules(&hand.to_string(), &rules)?, hand);
+        let mut evaluator = Evaluator::new(None, None, Vec::new(), Vec::new());
+        evaluator.set_rules(rules);
+        let evaluated = evaluator.evaluate(&ParsedHand::new(&hand), true, &vec![SituationYaku::self_pick()]).unwrap();
+        assert!(evaluated.yaku_list().contains(&"花牌 / Flower".to_string()));
+        assert_eq!(evaluated.score().han(), Han(3));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() -> Result<(), failure::Error> {
+        use crate::evaluate::Evaluator;
+
+        let hand = Hand::from_str("406m123p789s1z[0<55s]+1z tsumo")?;
+        let json = serde_json::to_value(&hand)?;
+        assert_eq!(json["tiles"], "456m123p789s11z");
+        assert_eq!(json["open_sets"][0]["call"]["source"], "left");
+        assert_eq!(json["red_fives"], "5m5s");
+        assert_eq!(serde_json::from_value::<Hand>(json)?, hand);
+
+        let evaluator = Evaluator::new(Some(Wind::East.tile()), Some(Wind::South.tile()), Vec::new(), Vec::new());
+        let evaluated = evaluator.evaluate_str("234m234p234s[2<34s]88p", false, &Vec::new())?.unwrap();
+        let json = serde_json::to_value(&evaluated)?;
+        assert_eq!(json["score"]["limit"], serde_json::Value::Null);
+        assert_eq!(json["score"]["payments"]["non_dealer"]["ron"], evaluated.score().ron_payment(false));
+        assert_eq!(json["yaku"][0]["id"], "all_simple");
+        assert_eq!(serde_json::from_value::<crate::evaluate::Evaluated>(json)?, evaluated);
+        Ok(())
+    }
+
+    #[test]
+    fn report() -> Result<(), failure::Error> {
+        use crate::evaluate::Evaluator;
+        use crate::output::{Report, CSV_HEADER};
+
+        let hand = Hand::from_str("234m234p234s[2<34s]88p")?;
+        let evaluator = Evaluator::new(Some(Wind::East.tile()), Some(Wind::South.tile()), Vec::new(), Vec::new());
+        let evaluated = evaluator.evaluate_all_str("234m234p234s[2<34s]88p", false, &Vec::new())?;
+        let report = Report::new(&hand, &evaluated, false);
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(lines.next(), Some("234m2348p234s[2<34s]+8p,8p,2,30,,2000,500,1000,all_simple;three_colour_straight"));
+
+        let json = report.to_json();
+        assert!(json.starts_with("[{\"hand\":\"234m2348p234s[2<34s]+8p\",\"winning\":\"8p\",\"dealer\":false,\"han\":2,\"fu\":30,\"limit\":null,"));
+        assert!(json.contains("{\"id\":\"all_simple\",\"name\":\"タンヤオ / All simple\"}"));
+        Ok(())
+    }
+
+    #[test]
+    fn svg() -> Result<(), failure::Error> {
+        use crate::svg::SvgRenderer;
+
+        let hand = Hand::from_str("34m44p[0^55s][+2>222p](7777z)+0m")?;
+        let svg = SvgRenderer::new(vec![Wind::North.tile()]).render(&hand);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>"));
+        // ドラ表示牌1枚 + 手牌4枚 + 当たり牌 + 面子11枚
+        assert_eq!(svg.matches("<rect").count(), 17);
+        // 鳴いた牌2枚と加えた牌1枚が横向き
+        assert_eq!(svg.matches("rotate(-90").count(), 3);
+        // 暗槓の両端が裏向き
+        assert_eq!(svg.matches("fill=\"#2e7d32\"").count(), 2);
+        // 赤五は萬子と索子の2枚 (数字と種類の文字)
+        assert_eq!(svg.matches("fill=\"#d32f2f\"").count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn terminal() -> Result<(), failure::Error> {
+        use crate::terminal::TerminalRenderer;
+
+        let hand = Hand::from_str("34m123p44p[0^55s](7777z)+0m")?;
+        let plain = TerminalRenderer::new(false).render_hand(&hand);
+        assert_eq!(plain, "┌──┐┌──┐┌──┐┌──┐┌──┐┌──┐┌──┐ ┌──┐ ┌──┐┌──┐┌──┐ ┌──┐┌──┐┌──┐┌──┐\n\
+                           │3m││4m││1p││2p││3p││4p││4p│ │0m│ │0s││5s││5s│ │▒▒││中││中││▒▒│\n\
+                           └──┘└──┘└──┘└──┘└──┘└──┘└──┘ └──┘ └──┘└──┘└──┘ └──┘└──┘└──┘└──┘");
+
+        let coloured = TerminalRenderer::new(true).render_tiles(&[Tile::Character(5), Tile::Circle(5)], &[Tile::Circle(5)]);
+        assert!(coloured.contains("\u{1b}[31m5m\u{1b}[0m"));
+        assert!(coloured.contains("\u{1b}[1;97;41m5p\u{1b}[0m"));
+
+        let parsed = ParsedHand::new(&hand);
+        let node = parsed.nodes.first().unwrap();
+        let lines: Vec<String> = TerminalRenderer::new(false).render_node(node, &hand.red_fives).lines().map(String::from).collect();
+        assert_eq!(lines[1], "│4p││4p│ │3m││4m││0m│ │1p││2p││3p│ │0s││5s││5s│ │▒▒││中││中││▒▒│");
+        Ok(())
+    }
+
+    #[test]
+    fn shanten() -> Result<(), failure::Error> {
+        use crate::shanten::{acceptance, discards, shanten};
+
+        let shanten_of = |s: &str| -> Result<i32, failure::Error> {
+            let (tiles, open_sets, _) = Hand::parse_tiles(s)?;
+            Ok(shanten(&tiles, &open_sets))
+        };
+        assert_eq!(shanten_of("123m456p789s11z222z")?, -1);
+        assert_eq!(shanten_of("123m456p789s11z22z")?, 0);
+        assert_eq!(shanten_of("1122m3344p5566s7z")?, 0);
+        assert_eq!(shanten_of("19m19p19s1234567z")?, 0);
+        assert_eq!(shanten_of("147m258p369s1234z")?, 6);
+        assert_eq!(shanten_of("13m[123p][456p](7777s)")?, 1);
+
+        let (tiles, open_sets, _) = Hand::parse_tiles("23m456p789s11z222z")?;
+        assert_eq!(acceptance(&tiles, &open_sets), vec![(Tile::Character(1), 4), (Tile::Character(4), 4)]);
+
+        let (tiles, open_sets, _) = Hand::parse_tiles("123m456p789s11z227z")?;
+        let best = &discards(&tiles, &open_sets)[0];
+        assert_eq!((best.tile.clone(), best.shanten, best.count()), (Dragon::Red.tile(), 0, 4));
+        Ok(())
+    }
+
+    #[test]
+    fn dora_and_rules() -> Result<(), failure::Error> {
+        use crate::evaluate::Evaluator;
+        use crate::rules::Rules;
+        use crate::score::Han;
+
+        assert_eq!(Tile::Character(9).dora(), Tile::Character(1));
+        assert_eq!(Wind::North.tile().dora(), Wind::East.tile());
+        assert_eq!(Dragon::Red.tile().dora(), Dragon::White.tile());
+        assert_eq!(Tile::all().len(), 34);
+        assert!(Tile::all().iter().enumerate().all(|(i, tile)| tile.index() == i));
+
+        // ドラ2枚と赤ドラ1枚
+        let evaluator = Evaluator::new(None, None, vec![Tile::Circle(8)], Vec::new());
+        let evaluated = evaluator.evaluate_str("234m234p406s[2<34s]88p", false, &Vec::new())?.unwrap();
+        assert_eq!(evaluated.score().han(), Han(5));
+        assert!(evaluated.yaku_list().contains(&"ドラ / Dora".to_string()));
+
+        // 赤ドラなし、喰いタンなし
+        let mut evaluator = Evaluator::new(None, None, vec![Tile::Circle(8)], Vec::new());
+        evaluator.set_rules(Rules::classic());
+        let evaluated = evaluator.evaluate_str("234m234p406s[2<34s]88p", false, &Vec::new())?.unwrap();
+        assert_eq!(evaluated.score().han(), Han(3));
+        assert!(!evaluated.yaku_list().contains(&"タンヤオ / All simple".to_string()));
+        assert!(Rules::preset("unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn chiitoitsu_fu() {
+        use crate::score::{Fu, Han, Score};
+
+        // 七対子の25符は30符に切り上げない
+        let score = Score::new(Han(2), Fu(25));
+        assert_eq!(score.ron_payment(false), 1600);
+        assert_eq!(score.ron_payment(true), 2400);
+        assert_eq!(Score::new(Han(2), Fu(32)).ron_payment(false), 2600);
+    }
+
+    #[test]
+    fn table() {
+        use crate::score::{Fu, Han, Score};
+        use crate::table::{is_possible, lookup, lookup_tsumo, ScoreTable};
+
+        let pairs = |entries: Vec<crate::table::Entry>| -> Vec<(u32, Option<u32>)> {
+            entries.iter().map(|entry| (entry.han.0, entry.fu.map(|fu| fu.0))).collect()
+        };
+        assert!(pairs(lookup(7700, false, false, false)).contains(&(3, Some(60))));
+        assert_eq!(pairs(lookup(1600, false, false, false)), vec![(1, Some(50)), (2, Some(25))]);
+        assert_eq!(pairs(lookup_tsumo((1300, 2600), false, false)), vec![(2, Some(80)), (3, Some(40)), (4, Some(20))]);
+        assert!(pairs(lookup(12000, true, false, false)).contains(&(5, None)));
+        // 複合役満も逆引きする
+        assert_eq!(pairs(lookup(96000, true, false, false)), vec![(26, None)]);
+        assert!(pairs(lookup(48000 * 7, true, false, false)).is_empty());
+        assert!(!is_possible(Han(1), Fu(20), true));
+        assert!(!is_possible(Han(2), Fu(20), false));
+        assert!(!is_possible(Han(2), Fu(25), true));
+        assert_eq!(Score::new(Han(2), Fu(25)).ron_payment(false), 1600);
+
+        let table = ScoreTable::new(false, false);
+        assert!(table.to_markdown().starts_with("| 符 | 1翻 | 2翻 | 3翻 | 4翻 |\n|---|---|---|---|---|\n| 20符 | - | - / 400-700 |"));
+        assert!(table.to_csv().contains("\n30符,1000 / 300-500,2000 / 500-1000,"));
+        assert!(table.to_text().lines().any(|line| line.trim_start().starts_with("満貫") && line.ends_with("8000 / 2000-4000")));
+        assert!(table.to_text().lines().any(|line| line.trim_start().starts_with("ダブル役満")));
+        assert!(table.to_text().lines().last().unwrap().trim_start().starts_with("6倍役満"));
+    }
+
+    #[test]
+    fn limit_boundaries() {
+        use crate::score::{Fu, Han, Score};
+        use crate::table::fu_values;
+
+        // 基本点を2000点で打ち切り、子は4倍、親は6倍して100点単位に切り上げる
+        for kiriage in [false, true] {
+            for han in 1..=4 {
+                for fu in fu_values() {
+                    let basic = fu.0 * 2u32.pow(han + 2);
+                    let mangan = basic >= 2000 || (kiriage && basic >= 1920);
+                    let score = Score::new_with_kiriage(Han(han), fu, kiriage);
+                    assert_eq!(score.limit_id() == Some("mangan"), mangan, "{}翻{} kiriage={}", han, fu, kiriage);
+                    for (is_dealer, multiple) in [(false, 4), (true, 6)] {
+                        let expected = if mangan { 2000 * multiple } else { (basic * multiple).div_ceil(100) * 100 };
+                        assert_eq!(score.ron_payment(is_dealer), expected, "{}翻{} kiriage={}", han, fu, kiriage);
+                    }
+                }
+            }
+        }
+
+        let ron = |han, fu, kiriage| Score::new_with_kiriage(Han(han), Fu(fu), kiriage).ron_payment(false);
+        assert_eq!(ron(1, 30, false), 1000);
+        assert_eq!(ron(2, 25, false), 1600);
+        assert_eq!(ron(2, 110, false), 7100);
+        assert_eq!(ron(3, 60, false), 7700);
+        assert_eq!(ron(3, 60, true), 8000);
+        assert_eq!(ron(3, 70, false), 8000);
+        assert_eq!(ron(4, 30, false), 7700);
+        assert_eq!(ron(4, 30, true), 8000);
+        assert_eq!(ron(4, 40, false), 8000);
+        assert_eq!(Score::new(Han(4), Fu(30)).ron_payment(true), 11600);
+        assert_eq!(Score::new(Han(4), Fu(20)).tsumo_payment(false), (1300, 2600));
+        assert_eq!(Score::new(Han(3), Fu(25)).tsumo_payment(false), (800, 1600));
+    }
+
+    #[test]
+    fn wall() {
+        use crate::wall::{Wall, WallTile};
+
+        let set = Wall::tile_set(true);
+        assert_eq!(set.len(), 136);
+        assert_eq!(set.iter().filter(|tile| tile.red).count(), 3);
+        assert!(Tile::all().iter().all(|tile| set.iter().filter(|each| &each.tile == tile).count() == 4));
+        assert_eq!(WallTile::red(Tile::Circle(5)).to_string(), "0p");
+
+        // 同じシードからは同じ山になる
+        let mut wall = Wall::new(42, true);
+        assert_eq!(wall, Wall::new(42, true));
+        assert_ne!(wall.tiles(), Wall::new(43, true).tiles());
+        assert_eq!(wall.seed(), Some(42));
+        let mut sorted = wall.tiles().clone();
+        sorted.sort();
+        let mut expected = set.clone();
+        expected.sort();
+        assert_eq!(sorted, expected);
+
+        assert_eq!(wall.remaining(), 122);
+        assert_eq!(wall.draw().as_ref(), wall.tiles().first());
+        assert_eq!(wall.dora_indicators(), vec![wall.tiles()[126].tile.clone()]);
+        assert_eq!(wall.ura_dora_indicators(), vec![wall.tiles()[131].tile.clone()]);
+
+        // 嶺上牌を引くと海底が手前にずれる
+        assert_eq!(wall.draw_rinshan().as_ref(), wall.tiles().get(122));
+        assert_eq!(wall.reveal_kan_dora().as_ref(), wall.tiles().get(127));
+        assert_eq!(wall.dora_indicators().len(), 2);
+        assert_eq!(wall.remaining(), 120);
+        (0..3).for_each(|_| { wall.draw_rinshan().unwrap(); });
+        assert_eq!(wall.draw_rinshan(), None);
+        (0..3).for_each(|_| { wall.reveal_kan_dora().unwrap(); });
+        assert_eq!(wall.reveal_kan_dora(), None);
+        (0..116).for_each(|_| { wall.draw().unwrap(); });
+        assert!(wall.is_last_tile());
+        assert_eq!(wall.draw().as_ref(), wall.tiles().get(117));
+        assert!(wall.is_exhausted());
+        assert_eq!(wall.draw(), None);
+
+        let replayed = Wall::from_tiles(Wall::new(42, true).tiles().clone()).unwrap();
+        assert_eq!(replayed.tiles(), Wall::new(42, true).tiles());
+        assert!(Wall::from_tiles(Vec::new()).is_err());
+    }
+
+    /// 配牌とツモ順を指定した山 (残りは整列した牌で埋める)
+    fn scripted_wall(hands: [&str; 4], draws: &str, dora_indicator: &str) -> crate::wall::Wall {
+        scripted_wall_from(crate::wall::Wall::tile_set(false), &hands, draws, dora_indicator)
+    }
+
+    /// 席順に配牌とツモを積み、残りは並び順のまま埋める
+    fn scripted_wall_from(mut rest: Vec<crate::wall::WallTile>, hands: &[&str], draws: &str, dora_indicator: &str) -> crate::wall::Wall {
+        use crate::wall::{Wall, WallTile};
+
+        let tiles = |s: &str| TilesNewType::from_str(s).unwrap().0;
+        let players = hands.len();
+        let mut placed: Vec<Option<WallTile>> = vec![None; rest.len()];
+        let mut take = |tile: &Tile| rest.remove(rest.iter().position(|each| &each.tile == tile).unwrap());
+        for (seat, hand) in hands.iter().enumerate() {
+            let positions = (0..12).map(|i| i / 4 * 4 * players + seat * 4 + i % 4).chain(std::iter::once(12 * players + seat));
+            for (position, tile) in positions.zip(tiles(hand).iter()) {
+                placed[position] = Some(take(tile));
+            }
+        }
+        for (i, tile) in tiles(draws).iter().enumerate() {
+            placed[13 * players + i] = Some(take(tile));
+        }
+        let dora = placed.len() - 10;
+        placed[dora] = Some(take(&tiles(dora_indicator)[0]));
+        let placed = placed.into_iter().map(|tile| tile.unwrap_or_else(|| rest.remove(0))).collect();
+        Wall::from_tiles(placed).unwrap()
+    }
+
+    #[test]
+    fn round() {
+        use crate::round::{Action, Phase, Round, RoundConfig, RoundResult};
+        use crate::wall::WallTile;
+
+        let hands = ["123m456p789s1122z", "234m567p234s5566s", "1111p2222p3333p9p", "6666m7777m13567z"];
+        // 親のツモ和了 (門前清自摸和、役牌 2翻30符)
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "1z", "9p")).unwrap();
+        assert_eq!(round.phase(), &Phase::Turn(0));
+        assert!(round.legal_actions(0).contains(&Action::Tsumo));
+        assert!(round.apply(1, Action::Pass).is_err());
+        round.apply(0, Action::Tsumo).unwrap();
+        match round.result() {
+            Some(RoundResult::Win { deltas, .. }) => assert_eq!(deltas, &vec![3000, -1000, -1000, -1000]),
+            result => panic!("{:?}", result),
+        }
+        assert_eq!(round.scores(), &vec![28000, 24000, 24000, 24000]);
+
+        // 親の立直が通り、下家の打牌を一発でロン
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "9m2z", "9p")).unwrap();
+        let nine = WallTile::new(Tile::Character(9));
+        assert!(!round.legal_actions(0).contains(&Action::Riichi(WallTile::new(Tile::Character(1)))));
+        round.apply(0, Action::Riichi(nine)).unwrap();
+        assert_eq!((round.scores()[0], round.riichi_sticks()), (24000, 1));
+        assert!(round.player(0).riichi);
+        assert_eq!(round.legal_actions(0), Vec::new());
+        round.apply(1, Action::Discard(WallTile::new(Wind::South.tile()))).unwrap();
+        assert_eq!(round.waiting(), vec![0]);
+        round.apply(0, Action::Ron).unwrap();
+        match round.result() {
+            Some(RoundResult::Win { wins, .. }) => {
+                let win = &wins[0];
+                assert_eq!((win.seat, win.from), (0, Some(1)));
+                assert!(win.evaluated.yaku_list().contains(&"一発 / One-shot".to_string()));
+                assert_eq!(win.deltas[0], -win.deltas[1] + 1000);
+            }
+            result => panic!("{:?}", result),
+        }
+        assert_eq!(round.riichi_sticks(), 0);
+
+        // 子のロン (タンヤオ 1翻40符)
+        let hands = ["123m456p789s1166s", "234m567p234s5566s", "1111p2222p3333p9p", "6666m7777m13567z"];
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "9m", "9p")).unwrap();
+        round.apply(0, Action::Discard(WallTile::new(Tile::Bamboo(6)))).unwrap();
+        let six = WallTile::new(Tile::Bamboo(6));
+        assert_eq!(round.legal_actions(1), vec![Action::Ron, Action::Pung(six.clone(), six),
+            Action::Chow(WallTile::new(Tile::Bamboo(4)), WallTile::new(Tile::Bamboo(5))), Action::Pass]);
+        round.apply(1, Action::Ron).unwrap();
+        match round.result() {
+            Some(RoundResult::Win { deltas, .. }) => assert_eq!(deltas, &vec![-1300, 1300, 0, 0]),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn multiple_ron() {
+        use crate::round::{AbortiveDraw, Action, Round, RoundConfig, RoundResult};
+        use crate::wall::WallTile;
+
+        // 親の4mを下家(23m)と対面(56m)が両方ロンできる (どちらも平和、タンヤオ 2翻30符)
+        let hands = ["123m4m789p789s123z", "23m345p567p234s66s", "56m345p678p456s88s", "999m111s999s1567z"];
+        let config = RoundConfig { honba: 1, riichi_sticks: 1, ..RoundConfig::default() };
+        let ron = |rules: crate::rules::Rules| {
+            let config = RoundConfig { rules, ..config.clone() };
+            let mut round = Round::with_wall(config, scripted_wall(hands, "9m", "9p")).unwrap();
+            round.apply(0, Action::Discard(WallTile::new(Tile::Character(4)))).unwrap();
+            assert_eq!(round.waiting(), vec![1, 2]);
+            round.apply(2, Action::Ron).unwrap();
+            round.apply(1, Action::Ron).unwrap();
+            round.result().unwrap().clone()
+        };
+
+        // 積み棒と供託は放銃者から近い下家が受け取る
+        match ron(crate::rules::Rules::standard()) {
+            RoundResult::Win { wins, deltas } => {
+                assert_eq!(wins.iter().map(|win| win.seat).collect::<Vec<_>>(), vec![1, 2]);
+                assert_eq!(wins[0].deltas, vec![-2300, 3300, 0, 0]);
+                assert_eq!(wins[1].deltas, vec![-2000, 0, 2000, 0]);
+                assert_eq!(deltas, vec![-4300, 3300, 2000, 0]);
+            }
+            result => panic!("{:?}", result),
+        }
+
+        // 頭ハネ
+        let rules = crate::rules::Rules { multiple_ron: false, ..crate::rules::Rules::standard() };
+        match ron(rules) {
+            RoundResult::Win { wins, deltas } => {
+                assert_eq!(wins.len(), 1);
+                assert_eq!(deltas, vec![-2300, 3300, 0, 0]);
+            }
+            result => panic!("{:?}", result),
+        }
+
+        // 席3も4m単騎(一気通貫)で待っていると三家和
+        let hands = [hands[0], hands[1], hands[2], "123p456p789p123s4m"];
+        let triple_ron = |rules: crate::rules::Rules| {
+            let config = RoundConfig { rules, ..config.clone() };
+            let mut round = Round::with_wall(config, scripted_wall(hands, "9m", "9p")).unwrap();
+            round.apply(0, Action::Discard(WallTile::new(Tile::Character(4)))).unwrap();
+            assert_eq!(round.waiting(), vec![1, 2, 3]);
+            (1..4).for_each(|seat| round.apply(seat, Action::Ron).unwrap());
+            round
+        };
+        let round = triple_ron(crate::rules::Rules::standard());
+        assert_eq!(round.result(), Some(&RoundResult::AbortiveDraw(AbortiveDraw::TripleRon)));
+        assert_eq!((round.scores(), round.riichi_sticks()), (&vec![25000; 4], 1));
+
+        let round = triple_ron(crate::rules::Rules { triple_ron_draw: false, ..crate::rules::Rules::standard() });
+        match round.result() {
+            Some(RoundResult::Win { wins, deltas }) => {
+                assert_eq!(wins.iter().map(|win| win.seat).collect::<Vec<_>>(), vec![1, 2, 3]);
+                assert_eq!(wins[0].deltas, vec![-2300, 3300, 0, 0]);
+                assert_eq!(deltas[0], 1000 - deltas[1..].iter().sum::<i32>());
+            }
+            result => panic!("{:?}", result),
+        }
+        assert_eq!(round.riichi_sticks(), 0);
+    }
+
+    #[test]
+    fn pao() {
+        use crate::round::{Action, Phase, Round, RoundConfig, RoundResult, Seat};
+        use crate::rules::Rules;
+        use crate::wall::WallTile;
+
+        // 席0が捨てた白、發、中を席1が順にポンして大三元の中単騎(9p)を聴牌する
+        let hands = ["246m2468p246s567z", "123m19p1s9s556677z", "357m357p357s1234z", "888m888p888s1234z"];
+        let discard = |round: &mut Round, seat: Seat, tile: Option<&str>, call: Option<Action>| {
+            let tile = match tile {
+                Some(tile) => WallTile::new(TilesNewType::from_str(tile).unwrap().0[0].clone()),
+                None => round.drawn().unwrap().clone(),
+            };
+            round.apply(seat, Action::Discard(tile)).unwrap();
+            if let Some(call) = call {
+                round.apply(1, call).unwrap();
+            }
+            while let Phase::Discarded(_) = round.phase() {
+                let seat = round.waiting()[0];
+                round.apply(seat, Action::Pass).unwrap();
+            }
+        };
+        let play = |rules: Rules, draws: &str| {
+            let config = RoundConfig { rules, ..RoundConfig::default() };
+            let mut round = Round::with_wall(config, scripted_wall(hands, draws, "9p")).unwrap();
+            for (i, (dragon, fodder)) in [("5z", "1s"), ("6z", "9s"), ("7z", "1p")].iter().enumerate() {
+                let dragon = WallTile::new(TilesNewType::from_str(dragon).unwrap().0[0].clone());
+                discard(&mut round, 0, Some(&dragon.to_string()), Some(Action::Pung(dragon.clone(), dragon)));
+                discard(&mut round, 1, Some(fodder), None);
+                if i < 2 {
+                    discard(&mut round, 2, None, None);
+                    discard(&mut round, 3, None, None);
+                }
+            }
+            round
+        };
+
+        let yakuman = crate::score::Score::Yakuman.ron_payment(false) as i32;
+
+        // 責任者以外からのロンは責任者と放銃者の折半
+        let mut round = play(Rules::standard(), "9m9m9m1z1z2z2z9p");
+        round.apply(2, Action::Discard(WallTile::new(Tile::Circle(9)))).unwrap();
+        round.apply(1, Action::Ron).unwrap();
+        match round.result() {
+            Some(RoundResult::Win { deltas, .. }) => assert_eq!(deltas, &vec![-yakuman / 2, yakuman, -yakuman / 2, 0]),
+            result => panic!("{:?}", result),
+        }
+
+        // ツモ和了は責任者が全額を支払う
+        let tsumo = |rules: Rules| {
+            let mut round = play(rules, "9m9m9m1z1z2z2z3z3z4z9p");
+            discard(&mut round, 2, None, None);
+            discard(&mut round, 3, None, None);
+            discard(&mut round, 0, None, None);
+            round.apply(1, Action::Tsumo).unwrap();
+            match round.result() {
+                Some(RoundResult::Win { deltas, .. }) => deltas.clone(),
+                result => panic!("{:?}", result),
+            }
+        };
+        assert_eq!(tsumo(Rules::standard()), vec![-yakuman, yakuman, 0, 0]);
+        assert_eq!(tsumo(Rules { pao: false, ..Rules::standard() }), vec![-yakuman / 2, yakuman, -yakuman / 4, -yakuman / 4]);
+
+        // 席0が捨てた東南西北を席1が順にポンして大四喜の9p単騎を聴牌し、席2からロン
+        let hands = ["258m258p258s1234z", "19m19s9p11223344z", "3467m357p357s66s9p", "888m888p888s1357p"];
+        let big_four_winds = |rules: Rules| {
+            let config = RoundConfig { rules, ..RoundConfig::default() };
+            let mut round = Round::with_wall(config, scripted_wall(hands, "9m", "9p")).unwrap();
+            for (i, (wind, fodder)) in [("1z", "1m"), ("2z", "9m"), ("3z", "1s"), ("4z", "9s")].iter().enumerate() {
+                let wind = WallTile::new(TilesNewType::from_str(wind).unwrap().0[0].clone());
+                discard(&mut round, 0, Some(&wind.to_string()), Some(Action::Pung(wind.clone(), wind)));
+                discard(&mut round, 1, Some(fodder), None);
+                if i < 3 {
+                    discard(&mut round, 2, None, None);
+                    discard(&mut round, 3, None, None);
+                }
+            }
+            round.apply(2, Action::Discard(WallTile::new(Tile::Circle(9)))).unwrap();
+            round.apply(1, Action::Ron).unwrap();
+            round.waiting().into_iter().for_each(|seat| round.apply(seat, Action::Pass).unwrap());
+            match round.result() {
+                Some(RoundResult::Win { wins, deltas }) => {
+                    assert!(wins[0].evaluated.yaku_list().iter().any(|yaku| yaku.starts_with("大四喜")));
+                    deltas.clone()
+                }
+                result => panic!("{:?}", result),
+            }
+        };
+        assert_eq!(big_four_winds(Rules::standard()), vec![-yakuman / 2, yakuman, -yakuman / 2, 0]);
+        assert_eq!(big_four_winds(Rules { pao: false, ..Rules::standard() }), vec![0, yakuman, -yakuman, 0]);
+
+        // 席0の5mを席1が大明槓し、嶺上牌の7sでツモ和了 (断么九、嶺上開花 嵌張で2翻40符)
+        let hands = ["159m19p19s123456z", "555m234p678s4456s", "234m678m345p345s9p", "678m678p789s1177z"];
+        let kong_rinshan = |rules: Rules| {
+            let mut tiles = scripted_wall(hands, "9m", "9p").tiles().clone();
+            let i = (53..122).find(|i| tiles[*i].tile == Tile::Bamboo(7)).unwrap();
+            tiles.swap(i, 122);
+            let config = RoundConfig { rules, ..RoundConfig::default() };
+            let mut round = Round::with_wall(config, crate::wall::Wall::from_tiles(tiles).unwrap()).unwrap();
+            round.apply(0, Action::Discard(WallTile::new(Tile::Character(5)))).unwrap();
+            round.apply(1, Action::Kong).unwrap();
+            while let Phase::Discarded(_) = round.phase() {
+                let seat = round.waiting()[0];
+                round.apply(seat, Action::Pass).unwrap();
+            }
+            assert_eq!(round.drawn(), Some(&WallTile::new(Tile::Bamboo(7))));
+            round.apply(1, Action::Tsumo).unwrap();
+            match round.result() {
+                Some(RoundResult::Win { deltas, .. }) => deltas.clone(),
+                result => panic!("{:?}", result),
+            }
+        };
+        // 大明槓させた席が和了全体をロンと同じ点数で支払う
+        assert_eq!(kong_rinshan(Rules { pao_kong_rinshan: true, ..Rules::standard() }), vec![-2600, 2600, 0, 0]);
+        assert_eq!(kong_rinshan(Rules::standard()), vec![-1300, 2700, -700, -700]);
+    }
+
+    #[test]
+    fn round_self_play() {
+        use crate::rng::Rng;
+        use crate::round::{Action, Round, RoundConfig};
+
+        for seed in 0..20 {
+            let mut round = Round::new(RoundConfig::default(), seed).unwrap();
+            let mut rng = Rng::new(seed);
+            while !round.is_finished() {
+                let seat = round.waiting()[0];
+                let actions = round.legal_actions(seat);
+                let action = actions.iter().find(|action| matches!(action, Action::Tsumo | Action::Ron))
+                    .cloned().unwrap_or_else(|| actions[rng.below(actions.len())].clone());
+                round.apply(seat, action).unwrap();
+                for player in round.players() {
+                    let count = player.concealed.len() + 3 * player.melds.len();
+                    assert!(count == 13 || count == 14);
+                }
+            }
+            assert!(round.result().is_some());
+            let total: i32 = round.scores().iter().sum::<i32>() + round.riichi_sticks() as i32 * 1000;
+            assert_eq!(total, 100000);
+        }
+    }
+
+    #[test]
+    fn abortive_draws() {
+        use crate::payment::noten_penalty;
+        use crate::round::{AbortiveDraw, Action, Phase, Round, RoundConfig, RoundResult};
+        use crate::wall::WallTile;
+
+        assert_eq!(noten_penalty(&[true, false, false, false], 3000), vec![3000, -1000, -1000, -1000]);
+        assert_eq!(noten_penalty(&[true, false, true, false], 3000), vec![1500, -1500, 1500, -1500]);
+        assert_eq!(noten_penalty(&[false; 4], 3000), vec![0; 4]);
+
+        // 九種九牌は最初のツモでのみ宣言できる
+        let east = WallTile::new(Wind::East.tile());
+        let others = ["369m369p369s567z1z", "1247m1457p1478s1z", "3456m3456p3456s1z"];
+        let hands = ["19m19p19s1234567z", others[0], others[1], others[2]];
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "2m", "9p")).unwrap();
+        assert!(round.legal_actions(0).contains(&Action::NineTerminals));
+        round.apply(0, Action::NineTerminals).unwrap();
+        assert_eq!(round.result(), Some(&RoundResult::AbortiveDraw(AbortiveDraw::NineTerminals(0))));
+        assert_eq!(round.scores(), &vec![25000; 4]);
+
+        // 4人が1巡目に同じ風牌を捨てると四風連打
+        let hands = ["258m258p258s234z1z", others[0], others[1], others[2]];
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "9m9p9s7z", "9p")).unwrap();
+        assert!(!round.legal_actions(0).contains(&Action::NineTerminals));
+        for seat in 0..4 {
+            round.apply(seat, Action::Discard(east.clone())).unwrap();
+        }
+        assert_eq!(round.result(), Some(&RoundResult::AbortiveDraw(AbortiveDraw::FourWinds)));
+
+        let mut rules = crate::rules::Rules::standard();
+        rules.abortive_draws = false;
+        let config = RoundConfig { rules, ..RoundConfig::default() };
+        let mut round = Round::with_wall(config, scripted_wall(hands, "9m9p9s7z", "9p")).unwrap();
+        for seat in 0..4 {
+            round.apply(seat, Action::Discard(east.clone())).unwrap();
+        }
+        assert!(!round.is_finished());
+
+        let pass = |round: &mut Round| {
+            while let Phase::Discarded(_) = round.phase() {
+                let seat = round.waiting()[0];
+                round.apply(seat, Action::Pass).unwrap();
+            }
+        };
+
+        // 4人目の立直宣言牌が通ると四家立直 (供託は残る)
+        let hands = ["123m456p789s1122z", "234m567p234s5566s", "567m234p678s3344z", "678m678p345s6677z"];
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "9m9s1m9m", "9p")).unwrap();
+        for seat in 0..4 {
+            assert!(!round.is_finished());
+            let drawn = round.drawn().unwrap().clone();
+            round.apply(seat, Action::Riichi(drawn)).unwrap();
+            pass(&mut round);
+        }
+        assert_eq!(round.result(), Some(&RoundResult::AbortiveDraw(AbortiveDraw::FourRiichi)));
+        assert_eq!((round.scores(), round.riichi_sticks()), (&vec![24000; 4], 4));
+
+        // 2人以上で4回槓をして、その後の打牌が通ると四槓散了
+        let hands = ["123m456p789s1122z", "234m567p234s5566s", "1111p2222p3333p9p", "6666m7777m13567z"];
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "9m9s4s8s", "9p")).unwrap();
+        let discard = |round: &mut Round, seat: usize| {
+            let drawn = round.drawn().unwrap().clone();
+            round.apply(seat, Action::Discard(drawn)).unwrap();
+            pass(round);
+        };
+        discard(&mut round, 0);
+        discard(&mut round, 1);
+        for tile in ["1p", "2p", "3p"] {
+            round.apply(2, Action::ConcealedKong(TilesNewType::from_str(tile).unwrap().0[0].clone())).unwrap();
+        }
+        discard(&mut round, 2);
+        round.apply(3, Action::ConcealedKong(Tile::Character(6))).unwrap();
+        assert!(!round.legal_actions(3).contains(&Action::ConcealedKong(Tile::Character(7))));
+        assert!(!round.is_finished());
+        discard(&mut round, 3);
+        assert_eq!(round.result(), Some(&RoundResult::AbortiveDraw(AbortiveDraw::FourKongs)));
+
+        // 三人麻雀では3人の立直や風牌の連打では流局しない
+        let config = || RoundConfig { scores: vec![35000; 3], rules: crate::rules::Rules::sanma(), ..RoundConfig::default() };
+        let sanma_wall = |hands: &[&str], draws: &str| scripted_wall_from(crate::wall::Wall::sanma_tile_set(false), hands, draws, "9p");
+        let hands = ["123p456p789s1122z", "234p567p234s5566s", "678p678s345s3344z"];
+        let mut round = Round::with_wall(config(), sanma_wall(&hands, "9m9s1m")).unwrap();
+        for seat in 0..3 {
+            let drawn = round.drawn().unwrap().clone();
+            round.apply(seat, Action::Riichi(drawn)).unwrap();
+            pass(&mut round);
+        }
+        assert!(!round.is_finished());
+        assert_eq!(round.riichi_sticks(), 3);
+
+        let hands = ["258p258s369p369s1z", "147p147s369s789s1z", "258p258s147p999m1z"];
+        let mut round = Round::with_wall(config(), sanma_wall(&hands, "1m1m1m")).unwrap();
+        for seat in 0..3 {
+            round.apply(seat, Action::Discard(east.clone())).unwrap();
+            pass(&mut round);
+        }
+        assert!(!round.is_finished());
+    }
+
+    #[test]
+    fn exhaustive_draw() {
+        use crate::payment::Stakes;
+        use crate::round::{Action, Phase, Round, RoundConfig, RoundResult};
+        use crate::rules::Rules;
+        use crate::score::{Han, Score};
+        use crate::wall::Wall;
+
+        // 席1は5s8s待ち、席2は1mを4枚使った単騎で不聴、席0と席3は不聴
+        let hands = ["2468m2468p2468s3m", "345m345p345s5567s", "1111m234p567s789s", "357m357p468s1357z"];
+        // 席0は么九牌だけをツモ切りし、他の席は数牌から先にツモ切りする
+        let tiles = |s: &str| TilesNewType::from_str(s).unwrap().0;
+        let yaotyu = tiles("9m9m9m9m1p1p1p1p9p9p9p1s1s1s1s9s9s9s");
+        let mut rest: Vec<Tile> = Wall::tile_set(false).into_iter().map(|tile| tile.tile).collect();
+        for tile in hands.iter().flat_map(|hand| tiles(hand)).chain(yaotyu.iter().cloned()).chain(tiles("9p")) {
+            rest.remove(rest.iter().position(|each| each == &tile).unwrap());
+        }
+        rest.sort_by_key(|tile| tile.is_yaotyu());
+        let mut rest = rest.into_iter();
+        let draws: String = (0..70).map(|i| if i % 4 == 0 { yaotyu[i / 4].mpsz() } else { rest.next().unwrap().mpsz() }).collect();
+
+        let play = |rules: Rules| {
+            let config = RoundConfig { rules: rules.clone(), ..RoundConfig::default() };
+            let mut round = Round::with_wall(config, scripted_wall(hands, &draws, "9p")).unwrap();
+            while !round.is_finished() {
+                let seat = round.waiting()[0];
+                let action = match round.phase() {
+                    Phase::Turn(_) => Action::Discard(round.drawn().unwrap().clone()),
+                    _ => Action::Pass,
+                };
+                round.apply(seat, action).unwrap();
+            }
+            assert!(round.wall().is_exhausted());
+            round.result().unwrap().clone()
+        };
+
+        // 流し満貫は満貫のツモ和了と同じ支払い (親は4000点オール)
+        let stakes = Stakes::new(4, 0, 0, 0, &Rules::standard());
+        let mangan = stakes.win(&Score::Mangan { han: Han(5) }, 0, None);
+        assert_eq!(mangan, vec![12000, -4000, -4000, -4000]);
+        assert_eq!(play(Rules::standard()), RoundResult::ExhaustiveDraw {
+            tenpai: vec![false, true, false, false], nagashi: vec![0], deltas: mangan,
+        });
+
+        // 流し満貫なしの場合は不聴罰符
+        assert_eq!(play(Rules { nagashi_mangan: false, ..Rules::standard() }), RoundResult::ExhaustiveDraw {
+            tenpai: vec![false, true, false, false], nagashi: Vec::new(), deltas: vec![-1000, 3000, -1000, -1000],
+        });
+    }
+
+    #[test]
+    fn sanma() {
+        use crate::evaluate::Evaluator;
+        use crate::game::MatchConfig;
+        use crate::payment::Stakes;
+        use crate::rng::Rng;
+        use crate::round::{Action, Round, RoundConfig};
+        use crate::rules::Rules;
+        use crate::score::{Fu, Han, Score};
+        use crate::wall::Wall;
+
+        let tiles = Wall::sanma_tile_set(true);
+        assert_eq!(tiles.len(), 108);
+        assert!(!tiles.iter().any(|tile| tile.tile == Tile::Character(5)));
+        assert_eq!(tiles.iter().filter(|tile| tile.red).count(), 2);
+        let rules = Rules::sanma();
+        assert_eq!(rules.dora(&Tile::Character(1)), Tile::Character(9));
+        assert_eq!(rules.dora(&Tile::Character(9)), Tile::Character(1));
+        assert_eq!(rules.dora(&Wind::West.tile()), Wind::North.tile());
+
+        // 子の30符2翻ツモ (500-1000)、ツモ損なしでは北家の500を折半して加える
+        let score = Score::new(Han(2), Fu(30));
+        let stakes = Stakes::new(3, 0, 0, 0, &rules);
+        assert_eq!(stakes.win(&score, 1, None), vec![-1000, 1500, -500]);
+        let stakes = Stakes::new(3, 0, 0, 0, &Rules { tsumo_loss: false, ..rules.clone() });
+        assert_eq!(stakes.win(&score, 1, None), vec![-1300, 2100, -800]);
+
+        // ツモの積み棒は1人100点ずつ、ロンは放銃者が300点 (2本場)
+        let honba = |players: usize, rules: &Rules| Stakes::new(players, 0, 2, 0, rules);
+        assert_eq!(honba(4, &Rules::standard()).win(&score, 1, None), vec![-1200, 2600, -700, -700]);
+        assert_eq!(honba(4, &Rules::standard()).win(&score, 1, Some(2)), vec![0, 2600, -2600, 0]);
+        assert_eq!(honba(3, &rules).win(&score, 1, None), vec![-1200, 1900, -700]);
+        assert_eq!(honba(3, &rules).win(&score, 1, Some(2)), vec![0, 2600, -2600]);
+        // ツモ損なしでは北家の500点と積み棒200点を折半して400点ずつ加える
+        assert_eq!(honba(3, &Rules { tsumo_loss: false, ..rules.clone() }).win(&score, 1, None), vec![-1600, 2700, -1100]);
+
+        // 北抜きドラは1枚1翻 (北がドラの場合はさらに1翻)
+        let mut evaluator = Evaluator::new(Some(Wind::East.tile()), Some(Wind::South.tile()), vec![Wind::North.tile()], Vec::new());
+        evaluator.set_rules(rules.clone());
+        evaluator.set_nukidora(2);
+        let evaluated = evaluator.evaluate_str("123p456p789p11s234s", true, &vec![]).unwrap().unwrap();
+        assert!(evaluated.yaku_list().contains(&"抜きドラ / North dora".to_string()));
+        assert_eq!(evaluated.score().han(), Han(1 + 2 + 4));
+        assert!(Evaluator::new(None, Some(Wind::North.tile()), Vec::new(), Vec::new()).validate_winds().is_ok());
+        let mut evaluator = Evaluator::new(None, Some(Wind::North.tile()), Vec::new(), Vec::new());
+        evaluator.set_rules(rules.clone());
+        assert!(evaluator.validate_winds().is_err());
+
+        assert!(Round::new(RoundConfig { rules: rules.clone(), ..RoundConfig::default() }, 0).is_err());
+        let config = MatchConfig::sanma();
+        let mut nukidora = 0;
+        for seed in 0..20 {
+            let round_config = RoundConfig { scores: vec![35000; 3], rules: config.rules.clone(), ..RoundConfig::default() };
+            let mut round = Round::new(round_config, seed).unwrap();
+            assert_eq!(round.wall().tiles().len(), 108);
+            let mut rng = Rng::new(seed);
+            while !round.is_finished() {
+                let seat = round.waiting()[0];
+                let actions = round.legal_actions(seat);
+                assert!(!actions.iter().any(|action| matches!(action, Action::Chow(..))));
+                let action = actions.iter().find(|action| matches!(action, Action::Tsumo | Action::Ron | Action::Nukidora))
+                    .cloned().unwrap_or_else(|| actions[rng.below(actions.len())].clone());
+                round.apply(seat, action).unwrap();
+            }
+            nukidora += round.players().iter().map(|player| player.nukidora.len()).sum::<usize>();
+            let total: i32 = round.scores().iter().sum::<i32>() + round.riichi_sticks() as i32 * 1000;
+            assert_eq!(total, 105000);
+        }
+        assert!(nukidora > 0);
+    }
+
+    #[test]
+    fn agents() {
+        use crate::agent::{Agent, BaselineAgent, Observation};
+        use crate::game::{Length, MatchConfig};
+        use crate::round::{Action, Round, RoundConfig};
+        use crate::simulator::Simulator;
+        use crate::wall::WallTile;
+
+        // ツモ和了できれば和了し、聴牌する打牌では立直する
+        let hands = ["123m456p789s1122z", "234m567p234s5566s", "1111p2222p3333p9p", "6666m7777m13567z"];
+        let round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "1z", "9p")).unwrap();
+        assert_eq!(BaselineAgent.act(&Observation::new(&round, 0)), Action::Tsumo);
+        let round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "9m", "9p")).unwrap();
+        let observation = Observation::new(&round, 0);
+        assert_eq!(BaselineAgent.act(&observation), Action::Riichi(WallTile::new(Tile::Character(9))));
+        assert_eq!(observation.drawn, Some(&WallTile::new(Tile::Character(9))));
+        assert_eq!(Observation::new(&round, 1).drawn, None);
+
+        /// ツモ切りのみのエージェント
+        struct Tsumogiri;
+        impl Agent for Tsumogiri {
+            fn name(&self) -> String {
+                "tsumogiri".to_string()
+            }
+
+            fn act(&mut self, observation: &Observation) -> Action {
+                let legal = &observation.legal_actions;
+                match observation.drawn {
+                    _ if legal.contains(&Action::Pass) => Action::Pass,
+                    Some(drawn) if legal.contains(&Action::Discard(drawn.clone())) => Action::Discard(drawn.clone()),
+                    _ => legal[0].clone(),
+                }
+            }
+        }
+
+        let config = MatchConfig { length: Length::Tonpuusen, west_extension: false, ..MatchConfig::default() };
+        let simulator = Simulator::new(config);
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(Tsumogiri), Box::new(Tsumogiri), Box::new(Tsumogiri), Box::new(Tsumogiri)];
+        let stats = simulator.run(&mut agents, 2, 0).unwrap();
+        assert!(stats.iter().all(|stats| stats.games == 2 && stats.wins == 0 && stats.deal_ins == 0));
+        assert_eq!(stats.iter().map(|stats| stats.placements).sum::<usize>(), 20);
+        assert!(stats.iter().map(|stats| stats.score).sum::<f64>().abs() < 1e-9);
+        let names: Vec<&str> = stats.iter().map(|stats| stats.name.as_str()).collect();
+        assert_eq!(names, vec!["tsumogiri#1", "tsumogiri#2", "tsumogiri#3", "tsumogiri#4"]);
+        assert!(crate::simulator::stats_table(&stats).lines().nth(2).unwrap().starts_with("tsumogiri#2 "));
+        assert!(simulator.run(&mut agents[..3], 1, 0).is_err());
+    }
+
+    #[test]
+    fn record() {
+        use crate::record::{Event, Record, RoundRecord};
+        use crate::round::{Action, Round, RoundConfig};
+        use crate::wall::WallTile;
+
+        // 配牌、ツモ、ドラ、和了が記録され、文字列を経由しても同じ局を再現できる
+        let hands = ["123m456p789s1122z", "234m567p234s5566s", "1111p2222p3333p9p", "6666m7777m13567z"];
+        let mut round = Round::with_wall(RoundConfig::default(), scripted_wall(hands, "1z", "9p")).unwrap();
+        round.apply(0, Action::Tsumo).unwrap();
+        let mut record = Record::new();
+        record.push(&round);
+        let text = record.to_string();
+        assert!(text.starts_with("mahjong-paifu 1\nround 1z 0 0 0 25000,25000,25000,25000\n"));
+        assert!(text.contains("draw 0 1z\nact 0 tsumo\nwin 0 - "));
+        let parsed: Record = text.parse().unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.rounds[0].verify().unwrap().scores(), round.scores());
+        assert_eq!(parsed.rounds[0].round_at(0).unwrap().drawn(), Some(&WallTile::new(Wind::East.tile())));
+
+        // 点数を書き換えた牌譜は検証に失敗する
+        let mut tampered = parsed.rounds[0].clone();
+        if let Some(Event::Win { deltas, .. }) = tampered.events.last_mut() {
+            deltas.swap(0, 1);
+        }
+        assert!(tampered.verify().is_err());
+        assert!("mahjong-paifu 2\n".parse::<Record>().is_err());
+
+        // シードから積んだ局を、選べる行動を順に選んで最後まで進めて再生する
+        let mut round = Round::new(RoundConfig::default(), 7).unwrap();
+        let mut steps = 0;
+        while !round.is_finished() {
+            let seat = round.waiting()[0];
+            let legal = round.legal_actions(seat);
+            round.apply(seat, legal[steps % legal.len()].clone()).unwrap();
+            steps += 1;
+        }
+        let record = Record { rounds: vec![RoundRecord::from_round(&round)] };
+        let recorded = record.to_string().parse::<Record>().unwrap().rounds.remove(0);
+        assert_eq!(recorded.seed, Some(7));
+        assert_eq!(recorded.steps(), steps);
+        assert_eq!(recorded.verify().unwrap().result(), round.result());
+        let halfway = recorded.round_at(steps / 2).unwrap();
+        assert_eq!(halfway.events()[..], round.events()[..halfway.events().len()]);
+    }
+
+    #[test]
+    fn calls() {
+        use crate::calls::{self, Action, Allowed, WinContext};
+        use crate::groups::Source;
+        use crate::round::{Player, RiverTile};
+        use crate::rules::Rules;
+        use crate::wall::WallTile;
+
+        let player = |s: &str| {
+            let (TilesNewType(tiles), red) = TilesNewType::from_str_with_red(s).unwrap();
+            let mut red = red.into_iter();
+            let concealed = tiles.into_iter().map(|tile| match red.next() {
+                Some(red) if red == tile => WallTile::red(tile),
+                _ => WallTile::new(tile),
+            }).collect();
+            Player { concealed, ..Player::default() }
+        };
+        let tile = |s: &str| WallTile::new(TilesNewType::from_str(s).unwrap().0[0].clone());
+        let context = || WinContext {
+            prevalent_wind: Wind::East, seat_wind: Wind::South, dora: Vec::new(), ura_dora: Vec::new(),
+            rules: Rules::standard(), situation: Vec::new(), nukidora: 0,
+        };
+        let allowed = Allowed { call: true, kong: true, riichi: true, nine_terminals: false, nukidora: false };
+
+        // 赤五の有無でチーの組み合わせを区別する
+        let hand = player("0m5m67m2345p11z3z");
+        assert_eq!(calls::chows(&hand, &Tile::Character(4)), vec![
+            Action::Chow(tile("5m"), tile("6m")), Action::Chow(WallTile::red(Tile::Character(5)), tile("6m"))]);
+        assert_eq!(calls::chows(&hand, &Tile::Circle(6)), vec![Action::Chow(tile("4p"), tile("5p"))]);
+        assert_eq!(calls::pungs(&hand, &Wind::East.tile()), vec![Action::Pung(tile("1z"), tile("1z"))]);
+        assert_eq!(calls::responses(&hand, &tile("4m"), Source::Opposite, &context(), allowed), Vec::new());
+        assert_eq!(calls::responses(&hand, &tile("1z"), Source::Right, &context(), allowed),
+                   vec![Action::Pung(tile("1z"), tile("1z")), Action::Pass]);
+
+        // 5sと東のシャンポン待ち (5sでは役がないのでロンできない)
+        let tenpai = player("234m567p234s55s11z");
+        assert!(calls::can_ron(&tenpai, &tile("1z"), &context()));
+        assert!(!calls::can_ron(&tenpai, &tile("5s"), &context()));
+        let mut furiten = tenpai.clone();
+        furiten.river.push(RiverTile { tile: tile("5s"), riichi: false, tsumogiri: true, called: false });
+        assert!(calls::is_furiten(&furiten));
+        assert!(!calls::can_ron(&furiten, &tile("1z"), &context()));
+        assert_eq!(calls::waits(&tenpai), vec![Tile::Bamboo(5), Wind::East.tile()]);
+
+        let mut drawn = player("234m567p234s55s11z9m");
+        assert_eq!(calls::riichi_candidates(&drawn), vec![tile("9m")]);
+        drawn.riichi = true;
+        assert_eq!(calls::self_actions(&drawn, Some(&tile("9m")), &context(), allowed), vec![Action::Discard(tile("9m"))]);
+        let kong = player("2222m567p234s55s11z");
+        assert_eq!(calls::concealed_kongs(&kong, &tile("2m")), vec![Tile::Character(2)]);
+
+        // ロン > ポン > チー、同じ優先順位は打牌した席から近い順
+        let responses = vec![None, Some(Action::Chow(tile("5m"), tile("6m"))), Some(Action::Pung(tile("4m"), tile("4m"))), Some(Action::Pass)];
+        assert_eq!(calls::resolve(&responses, 0), Some((2, Action::Pung(tile("4m"), tile("4m")))));
+        let responses = vec![Some(Action::Ron), Some(Action::Pass), None, Some(Action::Ron)];
+        assert_eq!(calls::resolve(&responses, 2), Some((3, Action::Ron)));
+        assert_eq!(calls::resolve(&[None, Some(Action::Pass)], 0), None);
+    }
+
+    #[test]
+    fn game() {
+        use crate::game::{settle, Length, Match, MatchConfig};
+        use crate::round::Action;
+
+        let config = MatchConfig::default();
+        let standings = settle(&[25000, 32000, 25000, 18000], &config);
+        let ranks: Vec<(usize, i32)> = standings.iter().map(|standing| (standing.seat, standing.points)).collect();
+        assert_eq!(ranks, vec![(1, 32000), (0, 25000), (2, 25000), (3, 18000)]);
+        let scores: Vec<f64> = standings.iter().map(|standing| standing.score).collect();
+        assert_eq!(scores, vec![42.0, 5.0, -15.0, -32.0]);
+        assert_eq!(scores.iter().sum::<f64>(), 0.0);
+
+        // 東風戦を最後まで打つ
+        let config = MatchConfig { length: Length::Tonpuusen, ..MatchConfig::default() };
+        let mut game = Match::new(config, 7);
+        assert_eq!(game.round_name(), "東1局 0本場");
+        let mut rounds = 0;
+        while !game.is_finished() {
+            let dealer = game.dealer();
+            let mut round = game.next_round().unwrap();
+            assert_eq!(round.seat_wind(dealer), Wind::East);
+            while !round.is_finished() {
+                let seat = round.waiting()[0];
+                let actions = round.legal_actions(seat);
+                let action = actions.iter().find(|action| matches!(action, Action::Tsumo | Action::Ron))
+                    .cloned().unwrap_or_else(|| actions[0].clone());
+                round.apply(seat, action).unwrap();
+            }
+            game.finish_round(&round).unwrap();
+            rounds += 1;
+        }
+        assert!(rounds >= 4);
+        assert!(game.next_round().is_err());
+        assert_eq!(game.scores().iter().sum::<i32>(), 100000);
+        assert!(game.ranking_table().starts_with("順位"));
+    }
 }
@@ -0,0 +1,118 @@
+use crate::evaluate::Evaluated;
+use crate::groups::Hand;
+use crate::score::{Fu, Han, Score};
+use crate::yaku;
+
+/// CSVの見出し
+pub const CSV_HEADER: &str = "hand,winning,han,fu,limit,ron,tsumo_non_dealer,tsumo_dealer,yaku";
+
+/// 評価結果をJSONやCSVで出力する
+///
+/// 支払いは`is_dealer`で指定した和了者についてのものを出力する。符は切り上げた値を出力する
+pub struct Report<'a> {
+    /// 手牌
+    hand: &'a Hand,
+    /// 評価結果 (`Evaluator::evaluate_all`の結果など)
+    evaluated: &'a [Evaluated],
+    /// 和了者が親か否か
+    is_dealer: bool,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(hand: &'a Hand, evaluated: &'a [Evaluated], is_dealer: bool) -> Self {
+        Report { hand, evaluated, is_dealer }
+    }
+
+    /// 評価結果1件をJSONのオブジェクトとして出力する
+    ///
+    /// `{"hand": "123m456p789s1z[5^55p]+1z", "winning": "1z", "dealer": false, "han": 3, "fu": 40, "limit": null,
+    /// "payments": {"ron": 5200, "tsumo": {"non_dealer": 1300, "dealer": 2600}},
+    /// "yaku": [{"id": "honor_tiles", "name": "役牌 / Honor tiles"}], "node": "123m 456p 789s 11z [5^55p]"}`
+    pub fn json_object(&self, evaluated: &Evaluated) -> String {
+        let score = evaluated.score();
+        let (tsumo_non_dealer, tsumo_dealer) = score.tsumo_payment(self.is_dealer);
+        let yaku: Vec<String> = evaluated.yaku_list().iter()
+            .map(|name| format!("{{\"id\":{},\"name\":{}}}", json_string(&yaku::id(name)), json_string(name)))
+            .collect();
+        let Han(han) = score.han();
+        let Fu(fu) = evaluated.fu().round_up();
+        format!("{{\"hand\":{},\"winning\":{},\"dealer\":{},\"han\":{},\"fu\":{},\"limit\":{},\
+                 \"payments\":{{\"ron\":{},\"tsumo\":{{\"non_dealer\":{},\"dealer\":{}}}}},\"yaku\":[{}],\"node\":{}}}",
+                json_string(&self.hand.to_mpsz()),
+                json_string(&self.hand.winning.mpsz()),
+                self.is_dealer,
+                han,
+                fu,
+                score.limit_id().map(json_string).unwrap_or_else(|| "null".to_string()),
+                score.ron_payment(self.is_dealer),
+                tsumo_non_dealer,
+                tsumo_dealer,
+                yaku.join(","),
+                json_string(&evaluated.node().to_string()))
+    }
+
+    /// 全ての評価結果をJSONの配列として出力する
+    pub fn to_json(&self) -> String {
+        let objects: Vec<String> = self.evaluated.iter().map(|evaluated| self.json_object(evaluated)).collect();
+        format!("[{}]", objects.join(","))
+    }
+
+    /// 評価結果1件をCSVの1行として出力する (`CSV_HEADER`の順、役は識別子を`;`で区切る)
+    pub fn csv_row(&self, evaluated: &Evaluated) -> String {
+        let score: &Score = evaluated.score();
+        let (tsumo_non_dealer, tsumo_dealer) = score.tsumo_payment(self.is_dealer);
+        let yaku: Vec<String> = evaluated.yaku_list().iter().map(|name| yaku::id(name)).collect();
+        let Han(han) = score.han();
+        let Fu(fu) = evaluated.fu().round_up();
+        [
+            self.hand.to_mpsz(),
+            self.hand.winning.mpsz(),
+            han.to_string(),
+            fu.to_string(),
+            score.limit_id().unwrap_or("").to_string(),
+            score.ron_payment(self.is_dealer).to_string(),
+            tsumo_non_dealer.to_string(),
+            tsumo_dealer.to_string(),
+            yaku.join(";"),
+        ].iter().map(|field| csv_field(field)).collect::<Vec<String>>().join(",")
+    }
+
+    /// 見出しと全ての評価結果をCSVとして出力する
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+        self.evaluated.iter().for_each(|evaluated| {
+            csv.push_str(&self.csv_row(evaluated));
+            csv.push('\n');
+        });
+        csv
+    }
+}
+
+/// JSONの文字列リテラル
+pub fn json_string(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len() + 2);
+    escaped.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// CSVのフィールド (`,`や`"`を含む場合は`"`で囲む)
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::groups::{Hand, OpenSet, Set, Tiles, TilesNewType};
+use crate::groups::{Hand, OpenSet, Set, Sets, Tiles, TilesNewType};
 use crate::tiles::Tile;
 use std::fmt::{Display, Formatter, Error};
 
@@ -11,6 +11,10 @@ pub struct ParsedHand {
     pub nodes: Vec<Node>,
     /// 当たり牌
     pub winning: Tile,
+    /// 赤五の枚数
+    pub red_fives: u32,
+    /// 花牌・季節牌の枚数
+    pub flowers: u32,
 }
 
 impl ParsedHand {
@@ -18,9 +22,10 @@ impl ParsedHand {
         let mut tiles = hand.tiles.clone();
         hand.open_sets.iter().for_each(|open| {
             let mut vec = match open {
-                OpenSet::Pung(vec) => vec,
-                OpenSet::Chow(vec) => vec,
-                OpenSet::Kong(vec) => vec,
+                OpenSet::Pung(vec, _) => vec,
+                OpenSet::Chow(vec, _) => vec,
+                OpenSet::Kong(vec, _) => vec,
+                OpenSet::AddedKong(vec, _) => vec,
                 OpenSet::ConcealedKong(vec) => vec,
             }.clone();
             tiles.append(&mut vec);
@@ -51,18 +56,22 @@ impl ParsedHand {
             }
             if sets.len() == 7 {
                 let node = Node { remaining: Vec::new(), open_sets: Vec::new(), sets, pong: Box::new(None), chow: Box::new(None) };
-                return ParsedHand { tiles, nodes: vec![node], winning: hand.winning.clone() };
+                return ParsedHand { tiles, nodes: vec![node], winning: hand.winning.clone(), red_fives: hand.red_fives.len() as u32, flowers: hand.flowers };
             }
         }
 
-        ParsedHand { tiles, nodes, winning: hand.winning.clone() }
+        ParsedHand { tiles, nodes, winning: hand.winning.clone(), red_fives: hand.red_fives.len() as u32, flowers: hand.flowers }
     }
 }
 
 impl Display for ParsedHand {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "input: ")?;
-        TilesNewType(self.tiles.clone()).fmt(f)?;
+        if f.alternate() {
+            TilesNewType(self.tiles.clone()).fmt(f)?;
+        } else {
+            write!(f, "{}", TilesNewType(self.tiles.clone()).to_mpsz(&mut Vec::new()))?;
+        }
         // 改行
         writeln!(f, "")?;
         self.nodes.iter().try_for_each(|node| {
@@ -120,8 +129,12 @@ impl Root {
             }
         }
 
-        // 国士
-        if heads.len() == 1 {
+        // 国士 (雀頭以外が12種の么九牌である場合のみ)
+        let thirteen_orphans = match heads.first() {
+            Some((_, tiles)) => heads.len() == 1 && hand.open_sets.is_empty() && tiles.len() == 12 && tiles.all_yaotyu(),
+            None => false,
+        };
+        if thirteen_orphans {
             let (head, tiles) = heads.get(0).unwrap();
             let head = Set::Pair(vec![head.clone(), head.clone()]);
             let body = Set::Chow(tiles.clone());
@@ -228,8 +241,18 @@ impl Node {
     }
 }
 
+/// `{}`は`Hand::from_str`で読み取れるMPSZ表記(`123m 456p 789s 11z [5^55p]`)、
+/// `{:#}`は牌の絵文字で出力する
 impl Display for Node {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if !f.alternate() {
+            let mut strings: Vec<String> = self.sets.iter()
+                .map(|set| TilesNewType(set.vec()).to_mpsz(&mut Vec::new()))
+                .collect();
+            self.open_sets.iter().for_each(|open_set| strings.push(open_set.to_string()));
+            return write!(f, "{}", strings.join(" "));
+        }
+
         // 手牌の面子を出力
         for set in &self.sets {
             set.fmt(f)?;
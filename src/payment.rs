@@ -0,0 +1,166 @@
+use crate::groups::{OpenSet, Source};
+use crate::round::Seat;
+use crate::rules::Rules;
+use crate::score::Score;
+use crate::tiles::{Honour, Tile};
+
+/// 点数の移動を決める卓の状況
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stakes {
+    /// 人数
+    pub players: usize,
+    /// 親の席
+    pub dealer: Seat,
+    /// 本場
+    pub honba: u32,
+    /// 供託のリーチ棒の本数
+    pub riichi_sticks: u32,
+    /// 1本場あたりの加算点 (ロン)
+    pub honba_value: u32,
+    /// 1本場あたりの1人の加算点 (ツモ)
+    pub tsumo_honba_value: u32,
+    /// リーチ棒1本の点数
+    pub riichi_deposit: u32,
+    /// ツモ損 (三人麻雀のツモ和了で北家の分を支払わない)
+    pub tsumo_loss: bool,
+}
+
+impl Stakes {
+    pub fn new(players: usize, dealer: Seat, honba: u32, riichi_sticks: u32, rules: &Rules) -> Self {
+        Stakes {
+            players, dealer, honba, riichi_sticks,
+            honba_value: rules.honba_value,
+            tsumo_honba_value: rules.tsumo_honba_value,
+            riichi_deposit: rules.riichi_deposit,
+            tsumo_loss: rules.tsumo_loss,
+        }
+    }
+
+    /// 和了による各席の点数の増減 (積み棒と供託を含む)
+    ///
+    /// `from`が`None`の場合はツモ和了とし、積み棒は各自が`tsumo_honba_value`ずつ支払う。三人麻雀で
+    /// ツモ損なしの場合は、いない北家の支払い(積み棒を含む)を残りの2人が折半して(100点単位に切り上げて)加える
+    pub fn win(&self, score: &Score, winner: Seat, from: Option<Seat>) -> Vec<i32> {
+        let is_dealer = winner == self.dealer;
+        let mut deltas = vec![0; self.players];
+        match from {
+            Some(from) => {
+                let amount = (score.ron_payment(is_dealer) + self.honba * self.honba_value) as i32;
+                deltas[from] -= amount;
+                deltas[winner] += amount;
+            }
+            None => {
+                let (non_dealer, dealer) = score.tsumo_payment(is_dealer);
+                let honba = self.honba * self.tsumo_honba_value;
+                let absent = if self.players == 3 && !self.tsumo_loss { (non_dealer + honba).div_ceil(200) * 100 } else { 0 };
+                for seat in (0..self.players).filter(|seat| *seat != winner) {
+                    let amount = (if seat == self.dealer { dealer } else { non_dealer } + absent + honba) as i32;
+                    deltas[seat] -= amount;
+                    deltas[winner] += amount;
+                }
+            }
+        }
+        deltas[winner] += (self.riichi_sticks * self.riichi_deposit) as i32;
+        deltas
+    }
+
+    /// 責任払いを含む和了による各席の点数の増減
+    ///
+    /// ツモ和了の場合は責任者が`pao.score`の全額と積み棒を支払い、責任者以外からのロンの場合は
+    /// 責任者と放銃者が折半して積み棒は放銃者が支払う。残りの点数は通常どおり支払う
+    pub fn win_with_pao(&self, score: &Score, winner: Seat, from: Option<Seat>, pao: Option<&Pao>) -> Vec<i32> {
+        let pao = match pao {
+            Some(pao) if pao.seat != winner && Some(pao.seat) != from => pao,
+            _ => return self.win(score, winner, from),
+        };
+        let mut deltas = match pao.remainder(score) {
+            Some(rest) => Stakes { honba: 0, riichi_sticks: 0, ..self.clone() }.win(&rest, winner, from),
+            None => vec![0; self.players],
+        };
+        let amount = pao.score.ron_payment(winner == self.dealer) as i32;
+        let honba = (self.honba * self.honba_value) as i32;
+        match from {
+            Some(from) => {
+                deltas[pao.seat] -= amount / 2;
+                deltas[from] -= amount - amount / 2 + honba;
+            }
+            None => deltas[pao.seat] -= amount + honba,
+        }
+        deltas[winner] += amount + honba + (self.riichi_sticks * self.riichi_deposit) as i32;
+        deltas
+    }
+
+    /// 1枚の打牌に対する複数のロン和了による各席の点数の増減
+    ///
+    /// `winners`は放銃者から近い順とし、積み棒と供託は最初の和了者のみが受け取る
+    pub fn multiple_ron(&self, winners: &[(Seat, &Score, Option<&Pao>)], from: Seat) -> Vec<Vec<i32>> {
+        winners.iter().enumerate().map(|(i, (winner, score, pao))| {
+            let stakes = if i == 0 { self.clone() } else { Stakes { honba: 0, riichi_sticks: 0, ..self.clone() } };
+            stakes.win_with_pao(score, *winner, Some(from), *pao)
+        }).collect()
+    }
+}
+
+/// 責任払い (包)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pao {
+    /// 責任を負う席
+    pub seat: Seat,
+    /// 責任の対象となる点数 (大三元・大四喜は役満1倍、大明槓の嶺上開花は和了全体)
+    pub score: Score,
+}
+
+impl Pao {
+    /// 和了の点数のうち責任の対象外の部分 (複合役満の残りなど)
+    fn remainder(&self, score: &Score) -> Option<Score> {
+        match (score, &self.score) {
+            (Score::MultipleYakuman { multiple }, Score::Yakuman) => Some(Score::yakuman(multiple - 1)),
+            _ => None,
+        }
+    }
+}
+
+/// 大三元・大四喜を確定させる最後の面子を鳴かせた席
+///
+/// `melds`は鳴いた順に並んでいるものとし、最後の三元牌(風牌)の面子が暗槓の場合は責任者なし
+pub fn pao_seat(melds: &[OpenSet], winner: Seat, players: usize) -> Option<Seat> {
+    let dragons: Vec<&OpenSet> = melds.iter().filter(|meld| matches!(meld_tile(meld), Tile::Honour(Honour::Dragon(_)))).collect();
+    let winds: Vec<&OpenSet> = melds.iter().filter(|meld| matches!(meld_tile(meld), Tile::Honour(Honour::Wind(_)))).collect();
+    let last = if dragons.len() == 3 {
+        dragons[2]
+    } else if winds.len() == 4 {
+        winds[3]
+    } else {
+        return None;
+    };
+    last.call().map(|call| source_seat(&call.source, winner, players))
+}
+
+fn meld_tile(meld: &OpenSet) -> &Tile {
+    match meld {
+        OpenSet::Pung(tiles, _) | OpenSet::Chow(tiles, _) | OpenSet::Kong(tiles, _)
+        | OpenSet::AddedKong(tiles, _) | OpenSet::ConcealedKong(tiles) => &tiles[0],
+    }
+}
+
+/// 鳴いた相手の席
+pub fn source_seat(source: &Source, seat: Seat, players: usize) -> Seat {
+    match source {
+        Source::Right => (seat + 1) % players,
+        Source::Opposite => (seat + 2) % players,
+        Source::Left => (seat + players - 1) % players,
+    }
+}
+
+/// 流局時の不聴罰符 (聴牌者が`total`を等分して受け取り、不聴者が等分して支払う)
+///
+/// 全員聴牌または全員不聴の場合は移動しない
+pub fn noten_penalty(tenpai: &[bool], total: u32) -> Vec<i32> {
+    let ready = tenpai.iter().filter(|tenpai| **tenpai).count() as i32;
+    let not_ready = tenpai.len() as i32 - ready;
+    if ready == 0 || not_ready == 0 {
+        return vec![0; tenpai.len()];
+    }
+    let total = total as i32;
+    tenpai.iter().map(|tenpai| if *tenpai { total / ready } else { -total / not_ready }).collect()
+}
@@ -0,0 +1,456 @@
+//! 牌譜
+//!
+//! 1行に1つの出来事を空白区切りで書く。先頭行は`mahjong-paifu <版>`で、局ごとに
+//! `round`行から始まり、ルール、シード、山、配牌、ツモ、行動、ドラ、立直、結果を順に並べる。
+//! 行動以外の行は局の進行によって決まるので、再生時に照合する
+//!
+//! ```text
+//! mahjong-paifu 1
+//! round 1z 0 0 0 25000,25000,25000,25000
+//! rules red_fives=true open_tanyao=true ...
+//! seed 42
+//! wall 1m 0p 7z ...
+//! deal 0 1m1m2m...
+//! draw 0 5p
+//! act 0 discard 5p
+//! act 1 pass
+//! win 1 0 2 30 -2000,2000,0,0
+//! ```
+
+use crate::calls::Action;
+use crate::groups::TilesNewType;
+use crate::round::{AbortiveDraw, Round, RoundConfig, RoundResult, Seat};
+use crate::rules::Rules;
+use crate::tiles::{Honour, Tile};
+use crate::wall::{Wall, WallTile};
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+/// 牌譜の版
+pub const VERSION: u32 = 1;
+
+/// 局の出来事
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// 配牌
+    Deal { seat: Seat, tiles: Vec<WallTile> },
+    /// ツモ (嶺上牌を含む)
+    Draw { seat: Seat, tile: WallTile },
+    /// 席の行動
+    Action { seat: Seat, action: Action },
+    /// ドラ表示牌 (最初のドラと槓ドラ)
+    Dora(WallTile),
+    /// 立直の成立 (供託を出す)
+    Riichi(Seat),
+    /// 和了 (翻、符、この和了による各席の点数の増減)
+    Win { seat: Seat, from: Option<Seat>, han: u32, fu: u32, deltas: Vec<i32> },
+    /// 荒牌流局
+    ExhaustiveDraw { tenpai: Vec<bool>, nagashi: Vec<Seat>, deltas: Vec<i32> },
+    /// 途中流局
+    AbortiveDraw(AbortiveDraw),
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Event::Deal { seat, tiles } => write!(f, "deal {} {}", seat, tiles.iter().map(mpsz).collect::<String>()),
+            Event::Draw { seat, tile } => write!(f, "draw {} {}", seat, mpsz(tile)),
+            Event::Action { seat, action } => write!(f, "act {} {}", seat, write_action(action)),
+            Event::Dora(tile) => write!(f, "dora {}", mpsz(tile)),
+            Event::Riichi(seat) => write!(f, "riichi {}", seat),
+            Event::Win { seat, from, han, fu, deltas } => {
+                let from = from.map_or("-".to_string(), |from| from.to_string());
+                write!(f, "win {} {} {} {} {}", seat, from, han, fu, join(deltas))
+            }
+            Event::ExhaustiveDraw { tenpai, nagashi, deltas } => {
+                let tenpai: String = tenpai.iter().map(|tenpai| if *tenpai { '1' } else { '0' }).collect();
+                let nagashi = if nagashi.is_empty() { "-".to_string() } else { join(nagashi) };
+                write!(f, "exhaustive {} {} {}", tenpai, nagashi, join(deltas))
+            }
+            Event::AbortiveDraw(abortive) => match abortive {
+                AbortiveDraw::NineTerminals(seat) => write!(f, "abort nine-terminals {}", seat),
+                AbortiveDraw::FourWinds => write!(f, "abort four-winds"),
+                AbortiveDraw::FourRiichi => write!(f, "abort four-riichi"),
+                AbortiveDraw::FourKongs => write!(f, "abort four-kongs"),
+                AbortiveDraw::TripleRon => write!(f, "abort triple-ron"),
+            },
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let token = |i: usize| tokens.get(i).copied().ok_or_else(|| format_err!("項目が足りません: {}", s));
+        let seat = |i: usize| -> Result<Seat, failure::Error> { number(token(i)?) };
+        let event = match token(0)? {
+            "deal" => Event::Deal { seat: seat(1)?, tiles: wall_tiles(token(2)?)? },
+            "draw" => Event::Draw { seat: seat(1)?, tile: wall_tile(token(2)?)? },
+            "act" => Event::Action { seat: seat(1)?, action: read_action(&tokens[2..])? },
+            "dora" => Event::Dora(wall_tile(token(1)?)?),
+            "riichi" => Event::Riichi(seat(1)?),
+            "win" => Event::Win {
+                seat: seat(1)?,
+                from: if token(2)? == "-" { None } else { Some(seat(2)?) },
+                han: number(token(3)?)?,
+                fu: number(token(4)?)?,
+                deltas: numbers(token(5)?)?,
+            },
+            "exhaustive" => Event::ExhaustiveDraw {
+                tenpai: token(1)?.chars().map(|c| c == '1').collect(),
+                nagashi: if token(2)? == "-" { Vec::new() } else { numbers(token(2)?)? },
+                deltas: numbers(token(3)?)?,
+            },
+            "abort" => Event::AbortiveDraw(match token(1)? {
+                "nine-terminals" => AbortiveDraw::NineTerminals(seat(2)?),
+                "four-winds" => AbortiveDraw::FourWinds,
+                "four-riichi" => AbortiveDraw::FourRiichi,
+                "four-kongs" => AbortiveDraw::FourKongs,
+                "triple-ron" => AbortiveDraw::TripleRon,
+                kind => return Err(format_err!("途中流局の種類が不正です: {}", kind)),
+            }),
+            kind => return Err(format_err!("不明な行です: {}", kind)),
+        };
+        if tokens.len() > event_len(&event) {
+            return Err(format_err!("余分な項目があります: {}", s));
+        }
+        Ok(event)
+    }
+}
+
+/// 行の項目数
+fn event_len(event: &Event) -> usize {
+    match event {
+        Event::Action { action, .. } => 2 + write_action(action).split_whitespace().count(),
+        Event::Win { .. } => 6,
+        Event::ExhaustiveDraw { .. } => 4,
+        Event::AbortiveDraw(AbortiveDraw::NineTerminals(_)) => 3,
+        Event::Deal { .. } | Event::Draw { .. } | Event::AbortiveDraw(_) => 3,
+        Event::Dora(_) | Event::Riichi(_) => 2,
+    }
+}
+
+fn write_action(action: &Action) -> String {
+    match action {
+        Action::Discard(tile) => format!("discard {}", mpsz(tile)),
+        Action::Riichi(tile) => format!("riichi {}", mpsz(tile)),
+        Action::Tsumo => "tsumo".to_string(),
+        Action::ConcealedKong(tile) => format!("ckong {}", tile.mpsz()),
+        Action::AddedKong(tile) => format!("akong {}", tile.mpsz()),
+        Action::Ron => "ron".to_string(),
+        Action::Chow(first, second) => format!("chow {} {}", mpsz(first), mpsz(second)),
+        Action::Pung(first, second) => format!("pung {} {}", mpsz(first), mpsz(second)),
+        Action::Kong => "kong".to_string(),
+        Action::Nukidora => "nukidora".to_string(),
+        Action::NineTerminals => "nine-terminals".to_string(),
+        Action::Pass => "pass".to_string(),
+    }
+}
+
+fn read_action(tokens: &[&str]) -> Result<Action, failure::Error> {
+    let token = |i: usize| tokens.get(i).copied().ok_or_else(|| format_err!("行動の項目が足りません: {}", tokens.join(" ")));
+    Ok(match token(0)? {
+        "discard" => Action::Discard(wall_tile(token(1)?)?),
+        "riichi" => Action::Riichi(wall_tile(token(1)?)?),
+        "tsumo" => Action::Tsumo,
+        "ckong" => Action::ConcealedKong(wall_tile(token(1)?)?.tile),
+        "akong" => Action::AddedKong(wall_tile(token(1)?)?.tile),
+        "ron" => Action::Ron,
+        "chow" => Action::Chow(wall_tile(token(1)?)?, wall_tile(token(2)?)?),
+        "pung" => Action::Pung(wall_tile(token(1)?)?, wall_tile(token(2)?)?),
+        "kong" => Action::Kong,
+        "nukidora" => Action::Nukidora,
+        "nine-terminals" => Action::NineTerminals,
+        "pass" => Action::Pass,
+        name => return Err(format_err!("不明な行動です: {}", name)),
+    })
+}
+
+/// MPSZ表記 (赤五は`0`)
+fn mpsz(tile: &WallTile) -> String {
+    if tile.red { format!("0{}", tile.tile.mpsz_parts().1) } else { tile.tile.mpsz() }
+}
+
+/// 牌の並び (`1m1m0p`など、赤五は`0`)
+fn wall_tiles(s: &str) -> Result<Vec<WallTile>, failure::Error> {
+    let mut tiles = Vec::new();
+    let mut nums = Vec::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            nums.push(c);
+            continue;
+        }
+        if nums.is_empty() {
+            return Err(format_err!("牌の指定が不正です: {}", s));
+        }
+        for num in nums.drain(..) {
+            let red = num == '0';
+            let TilesNewType(tile) = format!("{}{}", if red { '5' } else { num }, c).parse()?;
+            let tile = tile.into_iter().next().ok_or_else(|| format_err!("牌の指定が不正です: {}", s))?;
+            tiles.push(if red { WallTile::red(tile) } else { WallTile::new(tile) });
+        }
+    }
+    if !nums.is_empty() {
+        return Err(format_err!("牌の種類がありません: {}", s));
+    }
+    Ok(tiles)
+}
+
+/// 1枚の牌 (赤五は`0`)
+fn wall_tile(s: &str) -> Result<WallTile, failure::Error> {
+    let tiles = wall_tiles(s)?;
+    match tiles.as_slice() {
+        [tile] => Ok(tile.clone()),
+        _ => Err(format_err!("牌の指定が不正です: {}", s)),
+    }
+}
+
+fn number<T: FromStr>(s: &str) -> Result<T, failure::Error> {
+    s.parse().map_err(|_| format_err!("数値が不正です: {}", s))
+}
+
+fn numbers<T: FromStr>(s: &str) -> Result<Vec<T>, failure::Error> {
+    s.split(',').map(number).collect()
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",")
+}
+
+/// ルールを`名前=値`の並びで書く
+fn write_rules(rules: &Rules) -> String {
+    let flags = [
+        ("red_fives", rules.red_fives), ("open_tanyao", rules.open_tanyao), ("multiple_yakuman", rules.multiple_yakuman),
+        ("kiriage", rules.kiriage), ("abortive_draws", rules.abortive_draws), ("multiple_ron", rules.multiple_ron),
+        ("triple_ron_draw", rules.triple_ron_draw), ("pao", rules.pao), ("pao_kong_rinshan", rules.pao_kong_rinshan),
+        ("nagashi_mangan", rules.nagashi_mangan), ("sanma", rules.sanma), ("nukidora", rules.nukidora),
+        ("tsumo_loss", rules.tsumo_loss), ("flowers", rules.flowers),
+    ];
+    let values = [("noten_penalty", rules.noten_penalty), ("honba_value", rules.honba_value),
+                  ("tsumo_honba_value", rules.tsumo_honba_value), ("riichi_deposit", rules.riichi_deposit)];
+    let mut fields: Vec<String> = flags.iter().map(|(name, flag)| format!("{}={}", name, flag)).collect();
+    fields.extend(values.iter().map(|(name, value)| format!("{}={}", name, value)));
+    fields.join(" ")
+}
+
+/// `write_rules`の出力を読む (書かれていない項目は標準ルールの値とする)
+fn read_rules(tokens: &[&str]) -> Result<Rules, failure::Error> {
+    let mut rules = Rules::standard();
+    for token in tokens {
+        let (name, value) = token.split_once('=').ok_or_else(|| format_err!("ルールの指定が不正です: {}", token))?;
+        match name {
+            "red_fives" => rules.red_fives = number(value)?,
+            "open_tanyao" => rules.open_tanyao = number(value)?,
+            "multiple_yakuman" => rules.multiple_yakuman = number(value)?,
+            "kiriage" => rules.kiriage = number(value)?,
+            "abortive_draws" => rules.abortive_draws = number(value)?,
+            "multiple_ron" => rules.multiple_ron = number(value)?,
+            "triple_ron_draw" => rules.triple_ron_draw = number(value)?,
+            "pao" => rules.pao = number(value)?,
+            "pao_kong_rinshan" => rules.pao_kong_rinshan = number(value)?,
+            "nagashi_mangan" => rules.nagashi_mangan = number(value)?,
+            "sanma" => rules.sanma = number(value)?,
+            "nukidora" => rules.nukidora = number(value)?,
+            "tsumo_loss" => rules.tsumo_loss = number(value)?,
+            "flowers" => rules.flowers = number(value)?,
+            "noten_penalty" => rules.noten_penalty = number(value)?,
+            "honba_value" => rules.honba_value = number(value)?,
+            "tsumo_honba_value" => rules.tsumo_honba_value = number(value)?,
+            "riichi_deposit" => rules.riichi_deposit = number(value)?,
+            _ => return Err(format_err!("不明なルールです: {}", name)),
+        }
+    }
+    Ok(rules)
+}
+
+/// 1局の牌譜
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundRecord {
+    /// 局の開始時の状況
+    pub config: RoundConfig,
+    /// 山を積んだシード (与えられた山で始めた局は`None`)
+    pub seed: Option<u64>,
+    /// 積んだ順の山
+    pub wall: Vec<WallTile>,
+    pub events: Vec<Event>,
+}
+
+impl RoundRecord {
+    /// 局の進行から牌譜を作る
+    pub fn from_round(round: &Round) -> Self {
+        RoundRecord {
+            config: round.config().clone(),
+            seed: round.wall().seed(),
+            wall: round.wall().tiles().clone(),
+            events: round.events().clone(),
+        }
+    }
+
+    /// 行動の数
+    pub fn steps(&self) -> usize {
+        self.events.iter().filter(|event| matches!(event, Event::Action { .. })).count()
+    }
+
+    /// 最初の`step`個の行動を適用した局面を再現する
+    pub fn round_at(&self, step: usize) -> Result<Round, failure::Error> {
+        let mut round = Round::with_wall(self.config.clone(), Wall::from_tiles(self.wall.clone())?)?;
+        let actions = self.events.iter().filter_map(|event| match event {
+            Event::Action { seat, action } => Some((*seat, action.clone())),
+            _ => None,
+        });
+        for (seat, action) in actions.take(step) {
+            round.apply(seat, action)?;
+        }
+        Ok(round)
+    }
+
+    /// 全ての行動を再生し、和了の点数(翻、符、点数の移動)を含む出来事が牌譜と一致するか確かめる
+    ///
+    /// シードがあれば、シードから積んだ山が牌譜の山と一致するかも確かめる
+    pub fn verify(&self) -> Result<Round, failure::Error> {
+        if let Some(seed) = self.seed {
+            let round = Round::new(self.config.clone(), seed)?;
+            if round.wall().tiles() != &self.wall {
+                return Err(format_err!("シード{}から積んだ山が一致しません", seed));
+            }
+        }
+        let round = self.round_at(self.steps())?;
+        let replayed = round.events();
+        for (i, (recorded, replayed)) in self.events.iter().zip(replayed.iter()).enumerate() {
+            if recorded != replayed {
+                return Err(format_err!("{}番目の出来事が一致しません: 牌譜 `{}`、再生 `{}`", i + 1, recorded, replayed));
+            }
+        }
+        if self.events.len() != replayed.len() {
+            return Err(format_err!("出来事の数が一致しません: 牌譜 {}、再生 {}", self.events.len(), replayed.len()));
+        }
+        Ok(round)
+    }
+}
+
+impl Display for RoundRecord {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let config = &self.config;
+        writeln!(f, "round {} {} {} {} {}", config.prevalent_wind.clone().tile().mpsz(), config.dealer, config.honba, config.riichi_sticks, join(&config.scores))?;
+        writeln!(f, "rules {}", write_rules(&config.rules))?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed {}", seed)?;
+        }
+        writeln!(f, "wall {}", self.wall.iter().map(mpsz).collect::<Vec<String>>().join(" "))?;
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// 牌譜 (1局以上)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Record {
+    pub rounds: Vec<RoundRecord>,
+}
+
+impl Record {
+    pub fn new() -> Self {
+        Record::default()
+    }
+
+    /// 終わった局を加える
+    pub fn push(&mut self, round: &Round) {
+        self.rounds.push(RoundRecord::from_round(round));
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        writeln!(f, "mahjong-paifu {}", VERSION)?;
+        for round in &self.rounds {
+            write!(f, "{}", round)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Record {
+    type Err = failure::Error;
+
+    /// 空行と`#`以降は読み飛ばす
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap().trim()))
+            .filter(|(_, line)| !line.is_empty());
+        match lines.next() {
+            Some((_, line)) if line == format!("mahjong-paifu {}", VERSION) => {}
+            Some((_, line)) => return Err(format_err!("牌譜の版が不正です: {}", line)),
+            None => return Err(format_err!("牌譜が空です")),
+        }
+
+        let mut record = Record::new();
+        // 読んでいる局
+        let mut current: Option<RoundRecord> = None;
+        for (line_number, line) in lines {
+            let error = |error: failure::Error| format_err!("{}行目: {}", line_number, error);
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens[0] {
+                "round" => {
+                    let config = read_round(&tokens).map_err(error)?;
+                    record.rounds.extend(current.take());
+                    current = Some(RoundRecord { config, seed: None, wall: Vec::new(), events: Vec::new() });
+                }
+                "rules" | "seed" | "wall" => {
+                    let round = current.as_mut().ok_or_else(|| error(format_err!("round行がありません")))?;
+                    match tokens[0] {
+                        "rules" => round.config.rules = read_rules(&tokens[1..]).map_err(error)?,
+                        "seed" => round.seed = Some(tokens.get(1).map_or(Err(format_err!("シードがありません")), |seed| number(seed)).map_err(error)?),
+                        _ => round.wall = tokens[1..].iter().map(|tile| wall_tile(tile)).collect::<Result<_, _>>().map_err(error)?,
+                    }
+                }
+                _ => {
+                    let event = line.parse().map_err(error)?;
+                    let round = current.as_mut().ok_or_else(|| error(format_err!("round行がありません")))?;
+                    round.events.push(event);
+                }
+            }
+        }
+        record.rounds.extend(current);
+        Ok(record)
+    }
+}
+
+/// `round <場風> <親> <本場> <供託> <持ち点>`
+fn read_round(tokens: &[&str]) -> Result<RoundConfig, failure::Error> {
+    if tokens.len() != 6 {
+        return Err(format_err!("round行の項目数が不正です"));
+    }
+    let prevalent_wind = match wall_tile(tokens[1])?.tile {
+        Tile::Honour(Honour::Wind(wind)) => wind,
+        _ => return Err(format_err!("場風が不正です: {}", tokens[1])),
+    };
+    Ok(RoundConfig {
+        prevalent_wind,
+        dealer: number(tokens[2])?,
+        honba: number(tokens[3])?,
+        riichi_sticks: number(tokens[4])?,
+        scores: numbers(tokens[5])?,
+        rules: Rules::standard(),
+    })
+}
+
+/// 局の結果を牌譜の出来事にする
+pub fn result_events(result: &RoundResult) -> Vec<Event> {
+    match result {
+        RoundResult::Win { wins, .. } => wins.iter().map(|win| Event::Win {
+            seat: win.seat,
+            from: win.from,
+            han: win.evaluated.score().han().0,
+            fu: win.evaluated.fu().round_up().0,
+            deltas: win.deltas.clone(),
+        }).collect(),
+        RoundResult::ExhaustiveDraw { tenpai, nagashi, deltas } => {
+            vec![Event::ExhaustiveDraw { tenpai: tenpai.clone(), nagashi: nagashi.clone(), deltas: deltas.clone() }]
+        }
+        RoundResult::AbortiveDraw(abortive) => vec![Event::AbortiveDraw(abortive.clone())],
+    }
+}
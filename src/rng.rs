@@ -0,0 +1,43 @@
+/// シードから再現できる乱数生成器 (SplitMix64)
+///
+/// 外部クレートに依存せず、同じシードからは常に同じ列を返す
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// 次の64ビットの乱数
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// `0`以上`n`未満の一様な乱数 (`n`は1以上)
+    pub fn below(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        // 偏りが出ないように、nの倍数に収まらない端数を捨てる
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % n) as usize;
+            }
+        }
+    }
+
+    /// Fisher-Yatesで並べ替える
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
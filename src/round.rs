@@ -0,0 +1,792 @@
+use crate::calls::{self, Allowed, WinContext};
+use crate::evaluate::Evaluated;
+use crate::groups::{Call, Hand, OpenSet, Source};
+use crate::payment::{self, Pao, Stakes};
+use crate::record::{self, Event};
+use crate::rules::Rules;
+use crate::score::Score;
+use crate::shanten::{acceptance, shanten};
+use crate::tiles::{Tile, Wind};
+use crate::wall::{Wall, WallTile, DEAD_WALL, MAX_KONGS};
+use crate::yaku::situation::SituationYaku;
+
+pub use crate::calls::Action;
+
+/// 席 (0から人数-1まで)
+pub type Seat = usize;
+
+/// 局の開始時の状況
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundConfig {
+    /// 場風
+    pub prevalent_wind: Wind,
+    /// 親の席
+    pub dealer: Seat,
+    /// 本場
+    pub honba: u32,
+    /// 供託のリーチ棒の本数
+    pub riichi_sticks: u32,
+    /// 各席の持ち点 (人数分)
+    pub scores: Vec<i32>,
+    pub rules: Rules,
+}
+
+/// 東1局0本場、4人とも25000点
+impl Default for RoundConfig {
+    fn default() -> Self {
+        RoundConfig { prevalent_wind: Wind::East, dealer: 0, honba: 0, riichi_sticks: 0, scores: vec![25000; 4], rules: Rules::standard() }
+    }
+}
+
+/// 捨て牌
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiverTile {
+    pub tile: WallTile,
+    /// 立直宣言牌か
+    pub riichi: bool,
+    /// ツモ切りか
+    pub tsumogiri: bool,
+    /// 鳴かれたか
+    pub called: bool,
+}
+
+/// 各席の手牌と捨て牌
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Player {
+    /// 晒していない手牌 (ツモった牌を含む)
+    pub concealed: Vec<WallTile>,
+    /// 鳴いた面子 (暗槓を含む)
+    pub melds: Vec<OpenSet>,
+    /// 鳴いた面子に含まれる赤五
+    pub melded_red_fives: Vec<Tile>,
+    /// 抜いた北
+    pub nukidora: Vec<WallTile>,
+    /// 捨て牌
+    pub river: Vec<RiverTile>,
+    /// 立直しているか
+    pub riichi: bool,
+    /// ダブル立直か
+    pub double_riichi: bool,
+    /// 一発の権利があるか
+    pub ippatsu: bool,
+    /// 和了できる牌を見逃したか (同巡内、立直後は局の終わりまでフリテン)
+    pub missed_win: bool,
+}
+
+impl Player {
+    /// 晒していない手牌 (赤五を区別しない)
+    pub fn tiles(&self) -> Vec<Tile> {
+        self.concealed.iter().map(|tile| tile.tile.clone()).collect()
+    }
+
+    /// 聴牌か (待ちの牌を全て自分の手牌と晒した面子で使っている場合は不聴とする)
+    pub fn is_tenpai(&self) -> bool {
+        let tiles = self.tiles();
+        shanten(&tiles, &self.melds) == 0 && !acceptance(&tiles, &self.melds).is_empty()
+    }
+
+    /// 門前か (暗槓は門前とみなす)
+    pub fn is_closed(&self) -> bool {
+        self.melds.iter().all(|meld| matches!(meld, OpenSet::ConcealedKong(_)))
+    }
+
+    /// 赤五 (晒した牌を含む)
+    pub fn red_fives(&self) -> Vec<Tile> {
+        let mut red_fives: Vec<Tile> = self.concealed.iter().filter(|tile| tile.red).map(|tile| tile.tile.clone()).collect();
+        red_fives.extend(self.melded_red_fives.iter().cloned());
+        red_fives.sort();
+        red_fives
+    }
+
+    /// 手牌の中で`tile`と同じ牌 (赤五を区別して重複を除く)
+    pub fn distinct(&self, tile: &Tile) -> Vec<WallTile> {
+        let mut tiles: Vec<WallTile> = self.concealed.iter().filter(|each| &each.tile == tile).cloned().collect();
+        tiles.sort();
+        tiles.dedup();
+        tiles
+    }
+
+    /// 手牌の中で`tile`と同じ牌の枚数
+    pub fn count(&self, tile: &Tile) -> usize {
+        self.concealed.iter().filter(|each| &each.tile == tile).count()
+    }
+
+    /// 手牌から1枚取り除く
+    fn take(&mut self, tile: &WallTile) -> WallTile {
+        let i = self.concealed.iter().position(|each| each == tile).expect("手牌にない牌です");
+        self.concealed.remove(i)
+    }
+
+    /// 手牌から同じ牌を全て取り除く
+    fn take_all(&mut self, tile: &Tile) -> Vec<WallTile> {
+        let (taken, rest) = self.concealed.drain(..).partition(|each| &each.tile == tile);
+        self.concealed = rest;
+        taken
+    }
+
+    /// 晒した牌を面子として加える
+    fn meld(&mut self, tiles: &[WallTile], open_set: OpenSet) {
+        self.melded_red_fives.extend(tiles.iter().filter(|tile| tile.red).map(|tile| tile.tile.clone()));
+        self.melds.push(open_set);
+    }
+}
+
+/// 局の進行状況
+#[derive(Debug, Clone, PartialEq)]
+pub enum Phase {
+    /// 手番の席の行動待ち
+    Turn(Seat),
+    /// 打牌した席と、それに対する他家の応答待ち
+    Discarded(Seat),
+    /// 加槓した席と牌、それに対する槍槓の応答待ち
+    AddedKong(Seat, WallTile),
+    /// 終局
+    Finished,
+}
+
+/// 和了
+#[derive(Debug, Clone, PartialEq)]
+pub struct Win {
+    /// 和了した席
+    pub seat: Seat,
+    /// 放銃した席 (ツモ和了は`None`)
+    pub from: Option<Seat>,
+    pub hand: Hand,
+    pub evaluated: Evaluated,
+    /// 各席の点数の増減
+    pub deltas: Vec<i32>,
+}
+
+/// 局の結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundResult {
+    /// 和了 (ダブロン・トリロンの場合は放銃者から近い順に複数)
+    Win {
+        wins: Vec<Win>,
+        /// 各席の点数の増減の合計
+        deltas: Vec<i32>,
+    },
+    /// 荒牌流局
+    ExhaustiveDraw {
+        /// 各席が聴牌しているか
+        tenpai: Vec<bool>,
+        /// 流し満貫の席
+        nagashi: Vec<Seat>,
+        /// 各席の点数の増減 (不聴罰符または流し満貫)
+        deltas: Vec<i32>,
+    },
+    /// 途中流局
+    AbortiveDraw(AbortiveDraw),
+}
+
+/// 途中流局の種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum AbortiveDraw {
+    /// 九種九牌 (宣言した席)
+    NineTerminals(Seat),
+    /// 四風連打
+    FourWinds,
+    /// 四家立直
+    FourRiichi,
+    /// 四槓散了
+    FourKongs,
+    /// 三家和
+    TripleRon,
+}
+
+/// 1局の進行
+///
+/// 各席は`legal_actions`で選べる行動を受け取り、`apply`で行動する。打牌に対しては
+/// 応答できる全ての席が行動してから、ロン > ポン・大明槓 > チーの順に解決する
+#[derive(Debug, Clone)]
+pub struct Round {
+    /// 局の開始時の状況
+    config: RoundConfig,
+    prevalent_wind: Wind,
+    dealer: Seat,
+    honba: u32,
+    riichi_sticks: u32,
+    scores: Vec<i32>,
+    rules: Rules,
+    wall: Wall,
+    players: Vec<Player>,
+    phase: Phase,
+    /// 手番の席がツモった牌 (鳴いた後は`None`)
+    drawn: Option<WallTile>,
+    /// 直前のツモが嶺上牌か
+    rinshan: bool,
+    /// 立直を宣言して応答を待っている席と、ダブル立直か
+    pending_riichi: Option<(Seat, bool)>,
+    /// 打牌または加槓に対する応答
+    responses: Vec<Option<Action>>,
+    /// 1巡目で鳴きが入っていないか
+    uninterrupted: bool,
+    result: Option<RoundResult>,
+    /// 牌譜に書く出来事
+    events: Vec<Event>,
+}
+
+impl Round {
+    /// シードから山を積んで配牌する
+    pub fn new(config: RoundConfig, seed: u64) -> Result<Self, failure::Error> {
+        let wall = if config.rules.sanma { Wall::sanma(seed, config.rules.red_fives) } else { Wall::new(seed, config.rules.red_fives) };
+        Round::with_wall(config, wall)
+    }
+
+    /// 与えられた山で配牌する
+    ///
+    /// 親から順に4枚ずつ3回、1枚ずつ1回配り、親が最初のツモを行う。北抜きありのルールでは
+    /// 北抜きの分も嶺上牌を引けるようにする
+    pub fn with_wall(config: RoundConfig, wall: Wall) -> Result<Self, failure::Error> {
+        let players = config.scores.len();
+        if !(2..=4).contains(&players) {
+            return Err(format_err!("人数が不正です: {}人", players));
+        }
+        if config.rules.sanma && players != 3 {
+            return Err(format_err!("三人麻雀の人数が不正です: {}人", players));
+        }
+        let mut wall = if config.rules.nukidora { wall.with_nukidora() } else { wall };
+        if config.dealer >= players {
+            return Err(format_err!("親の席が不正です: {}", config.dealer));
+        }
+        let mut hands = vec![Player::default(); players];
+        for count in [4, 4, 4, 1] {
+            for i in 0..players {
+                let seat = (config.dealer + i) % players;
+                for _ in 0..count {
+                    let tile = wall.draw().ok_or_else(|| format_err!("配牌する牌が足りません"))?;
+                    hands[seat].concealed.push(tile);
+                }
+            }
+        }
+        let mut events: Vec<Event> = hands.iter().enumerate()
+            .map(|(seat, hand)| Event::Deal { seat, tiles: hand.concealed.clone() })
+            .collect();
+        events.push(Event::Dora(wall.tiles()[wall.tiles().len() - DEAD_WALL + MAX_KONGS].clone()));
+        let mut round = Round {
+            config: config.clone(),
+            prevalent_wind: config.prevalent_wind,
+            dealer: config.dealer,
+            honba: config.honba,
+            riichi_sticks: config.riichi_sticks,
+            scores: config.scores,
+            rules: config.rules,
+            wall,
+            players: hands,
+            phase: Phase::Finished,
+            drawn: None,
+            rinshan: false,
+            pending_riichi: None,
+            responses: vec![None; players],
+            uninterrupted: true,
+            result: None,
+            events,
+        };
+        round.draw(round.dealer);
+        Ok(round)
+    }
+
+    /// 局の開始時の状況
+    pub fn config(&self) -> &RoundConfig {
+        &self.config
+    }
+
+    /// 配牌からの出来事 (牌譜)
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    pub fn phase(&self) -> &Phase {
+        &self.phase
+    }
+
+    pub fn players(&self) -> &Vec<Player> {
+        &self.players
+    }
+
+    pub fn player(&self, seat: Seat) -> &Player {
+        &self.players[seat]
+    }
+
+    pub fn scores(&self) -> &Vec<i32> {
+        &self.scores
+    }
+
+    pub fn dealer(&self) -> Seat {
+        self.dealer
+    }
+
+    pub fn honba(&self) -> u32 {
+        self.honba
+    }
+
+    /// 供託のリーチ棒の本数 (和了者が受け取った後は0)
+    pub fn riichi_sticks(&self) -> u32 {
+        self.riichi_sticks
+    }
+
+    pub fn prevalent_wind(&self) -> &Wind {
+        &self.prevalent_wind
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn wall(&self) -> &Wall {
+        &self.wall
+    }
+
+    /// 手番の席がツモった牌
+    pub fn drawn(&self) -> Option<&WallTile> {
+        self.drawn.as_ref()
+    }
+
+    pub fn result(&self) -> Option<&RoundResult> {
+        self.result.as_ref()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase == Phase::Finished
+    }
+
+    /// 自風
+    pub fn seat_wind(&self, seat: Seat) -> Wind {
+        let winds = [Wind::East, Wind::South, Wind::West, Wind::North];
+        winds[(seat + self.players.len() - self.dealer) % self.players.len()].clone()
+    }
+
+    /// 行動を待っている席
+    pub fn waiting(&self) -> Vec<Seat> {
+        (0..self.players.len()).filter(|seat| !self.legal_actions(*seat).is_empty()).collect()
+    }
+
+    /// 席が選べる行動 (行動できない場合は空)
+    pub fn legal_actions(&self, seat: Seat) -> Vec<Action> {
+        match &self.phase {
+            Phase::Turn(turn) if *turn == seat => self.turn_actions(seat),
+            Phase::Discarded(from) | Phase::AddedKong(from, _) if *from != seat && self.responses[seat].is_none() => {
+                self.responses(seat)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// 行動する
+    pub fn apply(&mut self, seat: Seat, action: Action) -> Result<(), failure::Error> {
+        if seat >= self.players.len() || !self.legal_actions(seat).contains(&action) {
+            return Err(format_err!("行動できません: 席{} {:?}", seat, action));
+        }
+        self.events.push(Event::Action { seat, action: action.clone() });
+        match self.phase {
+            Phase::Turn(_) => self.apply_turn(seat, action),
+            _ => {
+                self.responses[seat] = Some(action);
+                self.resolve_if_ready()
+            }
+        }
+    }
+
+    /// 手番の行動
+    fn turn_actions(&self, seat: Seat) -> Vec<Action> {
+        calls::self_actions(&self.players[seat], self.drawn.as_ref(), &self.context(seat, true), self.allowed(seat))
+    }
+
+    /// 打牌または加槓に対する応答
+    fn responses(&self, seat: Seat) -> Vec<Action> {
+        let player = &self.players[seat];
+        match &self.phase {
+            Phase::Discarded(from) => {
+                let tile = &self.players[*from].river.last().unwrap().tile;
+                calls::responses(player, tile, self.source(seat, *from), &self.context(seat, false), self.allowed(seat))
+            }
+            Phase::AddedKong(_, tile) => calls::robbing_responses(player, tile, &self.context(seat, false)),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 卓の状況による制限
+    fn allowed(&self, seat: Seat) -> Allowed {
+        Allowed {
+            call: !self.wall.is_exhausted(),
+            kong: self.kongs().iter().sum::<usize>() < MAX_KONGS && !self.wall.is_exhausted(),
+            riichi: self.scores[seat] >= self.rules.riichi_deposit as i32 && self.wall.remaining() >= self.players.len(),
+            nine_terminals: self.rules.abortive_draws && self.uninterrupted && self.players[seat].river.is_empty(),
+            nukidora: self.rules.nukidora && !self.wall.is_exhausted(),
+        }
+    }
+
+    /// 各席の槓の数
+    fn kongs(&self) -> Vec<usize> {
+        self.players.iter().map(|player| player.melds.iter().filter(|meld| meld.is_kong()).count()).collect()
+    }
+
+    fn apply_turn(&mut self, seat: Seat, action: Action) -> Result<(), failure::Error> {
+        match action {
+            Action::Tsumo => {
+                let drawn = self.drawn.clone().unwrap();
+                let (hand, evaluated) = self.context(seat, true).evaluate(&self.players[seat], &drawn, true).unwrap();
+                self.finish_win(vec![(seat, hand, evaluated)], None);
+                Ok(())
+            }
+            Action::Discard(tile) => self.discard(seat, tile, false),
+            Action::Riichi(tile) => self.discard(seat, tile, true),
+            Action::ConcealedKong(tile) => {
+                let player = &mut self.players[seat];
+                let tiles = player.take_all(&tile);
+                player.meld(&tiles, OpenSet::ConcealedKong(vec![tile; 4]));
+                self.interrupt();
+                self.kong_draw(seat)
+            }
+            Action::AddedKong(tile) => {
+                let player = &mut self.players[seat];
+                let added = player.distinct(&tile).pop().unwrap();
+                player.take(&added);
+                let i = player.melds.iter().position(|meld| matches!(meld, OpenSet::Pung(tiles, _) if tiles[0] == tile)).unwrap();
+                let call = player.melds.remove(i).call().cloned();
+                player.meld(std::slice::from_ref(&added), OpenSet::AddedKong(vec![tile; 4], call));
+                self.interrupt();
+                self.drawn = None;
+                self.phase = Phase::AddedKong(seat, added);
+                self.responses = vec![None; self.players.len()];
+                self.resolve_if_ready()
+            }
+            Action::Nukidora => {
+                let player = &mut self.players[seat];
+                let north = player.take(&WallTile::new(Wind::North.tile()));
+                player.nukidora.push(north);
+                self.replacement_draw(seat)
+            }
+            Action::NineTerminals => {
+                self.finish_abortive(AbortiveDraw::NineTerminals(seat));
+                Ok(())
+            }
+            _ => Err(format_err!("手番では行動できません: {:?}", action)),
+        }
+    }
+
+    fn discard(&mut self, seat: Seat, tile: WallTile, riichi: bool) -> Result<(), failure::Error> {
+        let tsumogiri = self.drawn.as_ref() == Some(&tile);
+        let double = self.uninterrupted && self.players[seat].river.is_empty();
+        let player = &mut self.players[seat];
+        // 立直後にツモ和了を見送った場合もフリテンになる
+        if player.riichi && shanten(&player.tiles(), &player.melds) == -1 {
+            player.missed_win = true;
+        } else if !player.riichi {
+            player.missed_win = false;
+        }
+        player.take(&tile);
+        player.ippatsu = false;
+        player.river.push(RiverTile { tile, riichi, tsumogiri, called: false });
+        if riichi {
+            self.pending_riichi = Some((seat, double));
+        }
+        self.drawn = None;
+        self.rinshan = false;
+        self.phase = Phase::Discarded(seat);
+        self.responses = vec![None; self.players.len()];
+        self.resolve_if_ready()
+    }
+
+    /// 応答が揃っていれば解決する
+    fn resolve_if_ready(&mut self) -> Result<(), failure::Error> {
+        if !self.waiting().is_empty() {
+            return Ok(());
+        }
+        let players = self.players.len();
+        let (from, tile) = match &self.phase {
+            Phase::Discarded(from) => (*from, self.players[*from].river.last().unwrap().tile.clone()),
+            Phase::AddedKong(from, tile) => (*from, tile.clone()),
+            _ => return Ok(()),
+        };
+        // 和了できる牌を見逃した席はフリテンになる
+        for seat in (0..players).filter(|seat| *seat != from) {
+            if self.responses[seat] == Some(Action::Ron) {
+                continue;
+            }
+            let player = &mut self.players[seat];
+            let mut tiles = player.tiles();
+            tiles.push(tile.tile.clone());
+            if shanten(&tiles, &player.melds) == -1 {
+                player.missed_win = true;
+            }
+        }
+
+        let mut winners = calls::ron_seats(&self.responses, from);
+        if winners.len() == 3 && self.rules.triple_ron_draw {
+            self.finish_abortive(AbortiveDraw::TripleRon);
+            return Ok(());
+        }
+        if !winners.is_empty() {
+            if !self.rules.multiple_ron {
+                winners.truncate(1);
+            }
+            let wins = winners.into_iter().map(|winner| {
+                let (hand, evaluated) = self.context(winner, false).evaluate(&self.players[winner], &tile, false).unwrap();
+                (winner, hand, evaluated)
+            }).collect();
+            self.finish_win(wins, Some(from));
+            return Ok(());
+        }
+        match calls::resolve(&self.responses, from) {
+            _ if matches!(self.phase, Phase::AddedKong(..)) => self.kong_draw(from),
+            Some((seat, action)) => {
+                self.establish_riichi();
+                if let Some(abortive) = self.abortive_draw(false) {
+                    self.finish_abortive(abortive);
+                    return Ok(());
+                }
+                self.call(seat, from, action)
+            }
+            None => {
+                self.establish_riichi();
+                if let Some(abortive) = self.abortive_draw(true) {
+                    self.finish_abortive(abortive);
+                } else if self.wall.is_exhausted() {
+                    self.finish_draw();
+                } else {
+                    self.draw((from + 1) % players);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 打牌が通った時点の途中流局 (`passed`は誰も鳴かなかったか)
+    ///
+    /// 四家立直と四風連打は4人の場合のみとし、三人麻雀では四槓散了のみ
+    fn abortive_draw(&self, passed: bool) -> Option<AbortiveDraw> {
+        if !self.rules.abortive_draws {
+            return None;
+        }
+        let kongs = self.kongs();
+        if kongs.iter().sum::<usize>() == MAX_KONGS && !kongs.contains(&MAX_KONGS) {
+            return Some(AbortiveDraw::FourKongs);
+        }
+        if self.players.len() != 4 {
+            return None;
+        }
+        if self.players.iter().all(|player| player.riichi) {
+            return Some(AbortiveDraw::FourRiichi);
+        }
+        let winds: Vec<Tile> = [Wind::East, Wind::South, Wind::West, Wind::North].iter().map(|wind| wind.clone().tile()).collect();
+        let first = &self.players[self.dealer].river.first()?.tile.tile;
+        let four_winds = passed && self.uninterrupted && winds.contains(first)
+            && self.players.iter().all(|player| player.river.len() == 1 && &player.river[0].tile.tile == first);
+        if four_winds { Some(AbortiveDraw::FourWinds) } else { None }
+    }
+
+    /// 立直を成立させる (宣言牌が通った時)
+    fn establish_riichi(&mut self) {
+        if let Some((seat, double)) = self.pending_riichi.take() {
+            let player = &mut self.players[seat];
+            player.riichi = true;
+            player.double_riichi = double;
+            player.ippatsu = true;
+            self.scores[seat] -= self.rules.riichi_deposit as i32;
+            self.riichi_sticks += 1;
+            self.events.push(Event::Riichi(seat));
+        }
+    }
+
+    /// 鳴きが入った (一発と1巡目が消える)
+    fn interrupt(&mut self) {
+        self.uninterrupted = false;
+        self.players.iter_mut().for_each(|player| player.ippatsu = false);
+    }
+
+    /// 打牌を鳴く
+    fn call(&mut self, seat: Seat, from: Seat, action: Action) -> Result<(), failure::Error> {
+        let river = self.players[from].river.last_mut().unwrap();
+        river.called = true;
+        let called = river.tile.clone();
+        let source = self.source(seat, from);
+        let player = &mut self.players[seat];
+        let mut taken = match &action {
+            Action::Pung(first, second) | Action::Chow(first, second) => vec![player.take(first), player.take(second)],
+            _ => player.take_all(&called.tile),
+        };
+        taken.push(called.clone());
+        let mut tiles: Vec<Tile> = taken.iter().map(|tile| tile.tile.clone()).collect();
+        tiles.sort();
+        let call = Some(Call::new(called.tile, source));
+        let open_set = match action {
+            Action::Pung(..) => OpenSet::Pung(tiles, call),
+            Action::Chow(..) => OpenSet::Chow(tiles, call),
+            _ => OpenSet::Kong(tiles, call),
+        };
+        let is_kong = open_set.is_kong();
+        player.meld(&taken, open_set);
+        self.interrupt();
+        if is_kong {
+            return self.kong_draw(seat);
+        }
+        self.drawn = None;
+        self.rinshan = false;
+        self.phase = Phase::Turn(seat);
+        Ok(())
+    }
+
+    /// 鳴いた相手の位置
+    fn source(&self, seat: Seat, from: Seat) -> Source {
+        let players = self.players.len();
+        match (from + players - seat) % players {
+            1 => Source::Right,
+            n if n == players - 1 => Source::Left,
+            _ => Source::Opposite,
+        }
+    }
+
+    /// 山からツモる
+    fn draw(&mut self, seat: Seat) {
+        let tile = self.wall.draw().expect("山が尽きています");
+        self.events.push(Event::Draw { seat, tile: tile.clone() });
+        self.players[seat].concealed.push(tile.clone());
+        self.drawn = Some(tile);
+        self.rinshan = false;
+        self.phase = Phase::Turn(seat);
+    }
+
+    /// 槓ドラをめくって嶺上牌をツモる
+    fn kong_draw(&mut self, seat: Seat) -> Result<(), failure::Error> {
+        if let Some(indicator) = self.wall.reveal_kan_dora() {
+            self.events.push(Event::Dora(indicator));
+        }
+        self.replacement_draw(seat)
+    }
+
+    /// 嶺上牌をツモる (槓と北抜き)
+    fn replacement_draw(&mut self, seat: Seat) -> Result<(), failure::Error> {
+        let tile = self.wall.draw_rinshan().ok_or_else(|| format_err!("嶺上牌がありません"))?;
+        self.events.push(Event::Draw { seat, tile: tile.clone() });
+        self.players[seat].concealed.push(tile.clone());
+        self.drawn = Some(tile);
+        self.rinshan = true;
+        self.phase = Phase::Turn(seat);
+        Ok(())
+    }
+
+    /// 和了の判定に使う状況
+    fn context(&self, seat: Seat, draw: bool) -> WinContext {
+        let dora = |indicators: Vec<Tile>| indicators.iter().map(|tile| self.rules.dora(tile)).collect();
+        WinContext {
+            prevalent_wind: self.prevalent_wind.clone(),
+            seat_wind: self.seat_wind(seat),
+            dora: dora(self.wall.dora_indicators()),
+            ura_dora: if self.players[seat].riichi { dora(self.wall.ura_dora_indicators()) } else { Vec::new() },
+            rules: self.rules.clone(),
+            situation: self.situation(seat, draw),
+            nukidora: self.players[seat].nukidora.len() as u32,
+        }
+    }
+
+    /// 状況役
+    fn situation(&self, seat: Seat, draw: bool) -> Vec<SituationYaku> {
+        let player = &self.players[seat];
+        let mut situation = Vec::new();
+        if player.double_riichi {
+            situation.push(SituationYaku::double_ready());
+        } else if player.riichi {
+            situation.push(SituationYaku::ready());
+        }
+        if player.ippatsu {
+            situation.push(SituationYaku::one_shot());
+        }
+        if draw {
+            if player.is_closed() {
+                situation.push(SituationYaku::self_pick());
+            }
+            if self.rinshan {
+                situation.push(SituationYaku::dead_wall_draw());
+            } else if self.wall.is_exhausted() {
+                situation.push(SituationYaku::last_tile_from_the_wall());
+            }
+        } else {
+            match self.phase {
+                Phase::AddedKong(..) => situation.push(SituationYaku::robbing_a_quad()),
+                Phase::Discarded(_) if self.wall.is_exhausted() => situation.push(SituationYaku::last_discard()),
+                _ => {}
+            }
+        }
+        situation
+    }
+
+    /// 和了 (`wins`はロンの場合は放銃者から近い順)
+    fn finish_win(&mut self, wins: Vec<(Seat, Hand, Evaluated)>, from: Option<Seat>) {
+        let stakes = Stakes::new(self.players.len(), self.dealer, self.honba, self.riichi_sticks, &self.rules);
+        let paos: Vec<Option<Pao>> = wins.iter().map(|(seat, _, evaluated)| self.pao(*seat, evaluated, from)).collect();
+        let ledger = match from {
+            Some(from) => {
+                let winners: Vec<(Seat, &Score, Option<&Pao>)> = wins.iter().zip(paos.iter())
+                    .map(|((seat, _, evaluated), pao)| (*seat, evaluated.score(), pao.as_ref())).collect();
+                stakes.multiple_ron(&winners, from)
+            }
+            None => wins.iter().zip(paos.iter())
+                .map(|((seat, _, evaluated), pao)| stakes.win_with_pao(evaluated.score(), *seat, None, pao.as_ref())).collect(),
+        };
+        let mut deltas = vec![0; self.players.len()];
+        let wins = wins.into_iter().zip(ledger).map(|((seat, hand, evaluated), win_deltas)| {
+            deltas.iter_mut().zip(win_deltas.iter()).for_each(|(total, delta)| *total += delta);
+            Win { seat, from, hand, evaluated, deltas: win_deltas }
+        }).collect();
+        self.scores.iter_mut().zip(deltas.iter()).for_each(|(score, delta)| *score += delta);
+        self.riichi_sticks = 0;
+        self.result = Some(RoundResult::Win { wins, deltas });
+        self.events.extend(record::result_events(self.result.as_ref().unwrap()));
+        self.phase = Phase::Finished;
+    }
+
+    /// 責任払い
+    fn pao(&self, seat: Seat, evaluated: &Evaluated, from: Option<Seat>) -> Option<Pao> {
+        let melds = &self.players[seat].melds;
+        let players = self.players.len();
+        if self.rules.pao {
+            if let Some(liable) = payment::pao_seat(melds, seat, players) {
+                return Some(Pao { seat: liable, score: Score::Yakuman });
+            }
+        }
+        match melds.last() {
+            Some(OpenSet::Kong(_, Some(call))) if self.rules.pao_kong_rinshan && from.is_none() && self.rinshan => {
+                Some(Pao { seat: payment::source_seat(&call.source, seat, players), score: *evaluated.score() })
+            }
+            _ => None,
+        }
+    }
+
+    /// 荒牌流局 (流し満貫があれば満貫のツモ和了と同じ支払い、なければ不聴罰符)
+    fn finish_draw(&mut self) {
+        let tenpai: Vec<bool> = self.players.iter().map(Player::is_tenpai).collect();
+        let nagashi: Vec<Seat> = if self.rules.nagashi_mangan {
+            (0..self.players.len()).filter(|seat| self.is_nagashi(*seat)).collect()
+        } else {
+            Vec::new()
+        };
+        let deltas = if nagashi.is_empty() {
+            payment::noten_penalty(&tenpai, self.rules.noten_penalty)
+        } else {
+            let stakes = Stakes::new(self.players.len(), self.dealer, 0, 0, &self.rules);
+            let score = Score::Mangan { han: SituationYaku::nagashi_mangan().han_value() };
+            let mut deltas = vec![0; self.players.len()];
+            for seat in &nagashi {
+                stakes.win(&score, *seat, None).iter().enumerate().for_each(|(i, delta)| deltas[i] += delta);
+            }
+            deltas
+        };
+        self.scores.iter_mut().zip(deltas.iter()).for_each(|(score, delta)| *score += delta);
+        self.result = Some(RoundResult::ExhaustiveDraw { tenpai, nagashi, deltas });
+        self.events.extend(record::result_events(self.result.as_ref().unwrap()));
+        self.phase = Phase::Finished;
+    }
+
+    /// 流し満貫か (捨て牌が全て么九牌で、1枚も鳴かれていない)
+    fn is_nagashi(&self, seat: Seat) -> bool {
+        let river = &self.players[seat].river;
+        !river.is_empty() && river.iter().all(|tile| tile.tile.tile.is_yaotyu() && !tile.called)
+    }
+
+    fn finish_abortive(&mut self, abortive: AbortiveDraw) {
+        self.result = Some(RoundResult::AbortiveDraw(abortive));
+        self.events.extend(record::result_events(self.result.as_ref().unwrap()));
+        self.phase = Phase::Finished;
+    }
+}
@@ -0,0 +1,114 @@
+use crate::tiles::Tile;
+
+/// 採用するルール
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    /// 赤ドラを数えるか
+    pub red_fives: bool,
+    /// 喰いタン(鳴いたタンヤオ)を認めるか
+    pub open_tanyao: bool,
+    /// ダブル役満以上を認めるか (認めない場合は役満が重なっても1倍とする)
+    pub multiple_yakuman: bool,
+    /// 切り上げ満貫 (4翻30符と3翻60符を満貫とする)
+    pub kiriage: bool,
+    /// 途中流局 (九種九牌、四風連打、四家立直、四槓散了) を認めるか (三人麻雀では九種九牌と四槓散了のみ)
+    pub abortive_draws: bool,
+    /// ダブロン・トリロンを認めるか (認めない場合は放銃者から近い1人のみ和了する頭ハネ)
+    ///
+    /// 認める場合も、積み棒と供託は放銃者から近い和了者が全て受け取る (上家取り)
+    pub multiple_ron: bool,
+    /// 三家和を流局とするか
+    pub triple_ron_draw: bool,
+    /// 大三元・大四喜の責任払い(包)を認めるか
+    pub pao: bool,
+    /// 大明槓からの嶺上開花を槓させた人の責任払いとするか
+    pub pao_kong_rinshan: bool,
+    /// 流し満貫を認めるか
+    pub nagashi_mangan: bool,
+    /// 三人麻雀 (2m-8mを除いた108枚、チーなし、1mのドラ表示牌で9mがドラ)
+    pub sanma: bool,
+    /// 北抜きドラ (三人麻雀で北を抜いてドラとし、嶺上牌を引く)
+    pub nukidora: bool,
+    /// ツモ損 (三人麻雀のツモ和了で北家の分を支払わない)
+    pub tsumo_loss: bool,
+    /// 花牌・季節牌 (手牌の譜面に書けるようにし、1枚につき1翻のドラとする)
+    pub flowers: bool,
+    /// 流局時の不聴罰符の合計
+    pub noten_penalty: u32,
+    /// 1本場あたりの加算点 (ロン和了で放銃者が支払う)
+    pub honba_value: u32,
+    /// ツモ和了で和了者以外の1人が支払う1本場あたりの加算点
+    ///
+    /// 三人麻雀でツモ損ありの場合は2人分のみ受け取り、ツモ損なしの場合は北家の分を折半して加える
+    pub tsumo_honba_value: u32,
+    /// 供託のリーチ棒1本の点数
+    pub riichi_deposit: u32,
+}
+
+impl Rules {
+    /// 赤ドラあり、喰いタンあり、ダブル役満あり、切り上げ満貫なし、途中流局あり、ダブロンあり
+    pub fn standard() -> Self {
+        Rules {
+            red_fives: true,
+            open_tanyao: true,
+            multiple_yakuman: true,
+            kiriage: false,
+            abortive_draws: true,
+            multiple_ron: true,
+            triple_ron_draw: true,
+            pao: true,
+            pao_kong_rinshan: false,
+            nagashi_mangan: true,
+            sanma: false,
+            nukidora: false,
+            tsumo_loss: true,
+            flowers: false,
+            noten_penalty: 3000,
+            honba_value: 300,
+            tsumo_honba_value: 100,
+            riichi_deposit: 1000,
+        }
+    }
+
+    /// 赤ドラなし、喰いタンなし
+    pub fn classic() -> Self {
+        Rules { red_fives: false, open_tanyao: false, ..Rules::standard() }
+    }
+
+    /// 赤ドラあり、喰いタンあり、ダブル役満なし
+    pub fn single_yakuman() -> Self {
+        Rules { multiple_yakuman: false, ..Rules::standard() }
+    }
+
+    /// 三人麻雀 (北抜きドラあり、ツモ損あり、不聴罰符は合計2000点)
+    pub fn sanma() -> Self {
+        Rules { sanma: true, nukidora: true, noten_penalty: 2000, ..Rules::standard() }
+    }
+
+    /// ドラ表示牌に対応するドラ
+    pub fn dora(&self, indicator: &Tile) -> Tile {
+        if self.sanma { indicator.sanma_dora() } else { indicator.dora() }
+    }
+
+    /// プリセット名
+    pub fn preset_names() -> Vec<&'static str> {
+        vec!["standard", "classic", "single-yakuman", "sanma"]
+    }
+
+    /// プリセット名からルールを返す
+    pub fn preset(name: &str) -> Result<Self, failure::Error> {
+        match name {
+            "standard" => Ok(Rules::standard()),
+            "classic" => Ok(Rules::classic()),
+            "single-yakuman" => Ok(Rules::single_yakuman()),
+            "sanma" => Ok(Rules::sanma()),
+            _ => Err(format_err!("ルールのプリセットが不正です: {} ({}のいずれか)", name, Rules::preset_names().join(", "))),
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::standard()
+    }
+}
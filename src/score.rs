@@ -32,6 +32,14 @@ pub enum Score {
 
 impl Score {
     pub fn new(han: Han, fu: Fu) -> Self {
+        Score::new_with_kiriage(han, fu, false)
+    }
+
+    /// 翻数と符から得点を決める
+    ///
+    /// 4翻以下でも基本点(符×2^(翻+2))が2000点以上なら満貫とする。
+    /// `kiriage`が真の場合は4翻30符と3翻60符(基本点1920点)も満貫に切り上げる
+    pub fn new_with_kiriage(han: Han, fu: Fu, kiriage: bool) -> Self {
         let Han(han_value) = han;
         if han_value >= 13 as u32 {
             Score::KazoeYakuman { han }
@@ -41,13 +49,24 @@ impl Score {
             Score::Baiman { han }
         } else if (6..8).contains(&han_value) {
             Score::Haneman { han }
-        } else if (4..6).contains(&han_value) {
+        } else if han_value == 5 {
             Score::Mangan { han }
         } else {
-            Score::Other { han, fu }
+            let basic = Score::basic_points(han, fu);
+            if basic >= 2000 || (kiriage && basic >= 1920) {
+                Score::Mangan { han }
+            } else {
+                Score::Other { han, fu }
+            }
         }
     }
 
+    /// 基本点 (符×2^(翻+2)、満貫の上限なし)
+    pub fn basic_points(han: Han, fu: Fu) -> u32 {
+        let (Han(han), Fu(fu)) = (han, fu.round_up());
+        fu.saturating_mul(2u32.saturating_pow(han + 2))
+    }
+
     pub fn yakuman(multiple: u8) -> Self {
         if multiple == 1 {
             Score::Yakuman
@@ -102,6 +121,20 @@ impl Score {
         }
     }
 
+    /// 満貫以上の名前の識別子 (`"mangan"`など)
+    pub fn limit_id(&self) -> Option<&'static str> {
+        match &self {
+            Score::Other { .. } => None,
+            Score::Mangan { .. } => Some("mangan"),
+            Score::Haneman { .. } => Some("haneman"),
+            Score::Baiman { .. } => Some("baiman"),
+            Score::Sanbaiman { .. } => Some("sanbaiman"),
+            Score::Yakuman => Some("yakuman"),
+            Score::KazoeYakuman { .. } => Some("kazoe_yakuman"),
+            Score::MultipleYakuman { .. } => Some("multiple_yakuman"),
+        }
+    }
+
     pub fn score(&self, is_dealer: bool) -> u32 {
         match &self {
             Score::Mangan { .. } => {
@@ -138,19 +171,64 @@ impl Score {
                 score
             }
             Score::Other { han, fu } => {
-                let Fu(fu) = fu;
-                let fu = ((fu + (10 - 1)) / 10) * 10;
-                let Han(han) = han;
+                (if is_dealer { 6 } else { 4 }) * Score::basic_points(*han, *fu)
+            }
+        }
+    }
+}
 
-                let score =
-                    if is_dealer { 6 as u32 } else { 4 as u32 }
-                        * fu * u32::pow(2, han.clone() + 2);
-                score
+impl Score {
+    /// 翻数 (役満は1倍につき13翻として扱う)
+    pub fn han(&self) -> Han {
+        match &self {
+            Score::Mangan { han } => *han,
+            Score::Haneman { han } => *han,
+            Score::Baiman { han } => *han,
+            Score::Sanbaiman { han } => *han,
+            Score::KazoeYakuman { han } => *han,
+            Score::Other { han, .. } => *han,
+            Score::Yakuman => Han(13),
+            Score::MultipleYakuman { multiple } => Han(13 * *multiple as u32),
+        }
+    }
+
+    /// ロン和了時に放銃者が支払う点数
+    pub fn ron_payment(&self, is_dealer: bool) -> u32 {
+        ceil_hundred(self.score(is_dealer))
+    }
+
+    /// ツモ和了時の支払い (子の支払い, 親の支払い)
+    ///
+    /// 和了者が親の場合、親の支払いは0となる
+    pub fn tsumo_payment(&self, is_dealer: bool) -> (u32, u32) {
+        let basic = self.score(false) / 4;
+        if is_dealer {
+            (ceil_hundred(basic * 2), 0)
+        } else {
+            (ceil_hundred(basic), ceil_hundred(basic * 2))
+        }
+    }
+
+    /// 和了者が受け取る点数の合計
+    pub fn points(&self, is_dealer: bool, draw: bool) -> u32 {
+        if draw {
+            let (non_dealer, dealer) = self.tsumo_payment(is_dealer);
+            if is_dealer {
+                non_dealer * 3
+            } else {
+                non_dealer * 2 + dealer
             }
+        } else {
+            self.ron_payment(is_dealer)
         }
     }
 }
 
+/// 100点単位に切り上げる
+fn ceil_hundred(score: u32) -> u32 {
+    score.div_ceil(100) * 100
+}
+
 impl PartialOrd for Score {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         if self == other {
@@ -225,6 +303,18 @@ impl Display for Fu {
     }
 }
 
+impl Fu {
+    /// 10符単位に切り上げた符 (七対子の25符はそのまま)
+    pub fn round_up(&self) -> Fu {
+        let Fu(fu) = self;
+        if *fu == 25 {
+            Fu(25)
+        } else {
+            Fu(fu.div_ceil(10) * 10)
+        }
+    }
+}
+
 impl Add for Fu {
     type Output = Fu;
 
@@ -0,0 +1,460 @@
+//! `serde`による(デ)シリアライズ (`serde`フィーチャーが有効な場合のみ)
+//!
+//! JSONでの形は以下の通り。牌と牌の並びはMPSZ表記の文字列で表す。
+//!
+//! - `Tile`, `Honour`, `Wind`, `Dragon`: `"5m"`, `"1z"`
+//! - `Hand`: `{"tiles": "123m456p789s11z", "open_sets": [OpenSet], "winning": "1z", "draw": true | null, "red_fives": "5m", "flowers": 1}`
+//!   - `flowers`は花牌・季節牌がある場合のみ
+//! - `OpenSet`: `{"type": "chow" | "pung" | "kong" | "added_kong" | "concealed_kong", "tiles": "123s", "call": {"tile": "2s", "source": "left" | "opposite" | "right"} | null}`
+//! - `Set`: `{"type": "chow" | "pung" | "pair", "tiles": "123m"}`
+//! - `Node`: `{"sets": [Set], "open_sets": [OpenSet]}`
+//! - `Wait`: `{"type": "ryanmen" | "kanchan" | "penchan" | "tanki" | "shanpon", "fu": 30, "winning": "3m", "node": Node}`
+//! - `Han`, `Fu`: 数値
+//! - `Score`: `{"limit": "mangan" | ... | null, "han": 5, "fu": 30 | null, "multiple": 2, "payments": {"non_dealer": Payment, "dealer": Payment}}`
+//!   - `limit`は`mangan`, `haneman`, `baiman`, `sanbaiman`, `yakuman`, `kazoe_yakuman`, `multiple_yakuman`のいずれか。満貫未満は`null`
+//!   - `multiple`は役満の場合のみ
+//!   - `Payment`は`{"ron": 8000, "tsumo": {"non_dealer": 2000, "dealer": 4000}}` (ツモは各支払者の支払額)。読み込み時は無視する
+//! - `Evaluated`: `{"node": Node, "score": Score, "fu": 40, "yaku": [{"id": "no_points_hand", "name": "平和 / No-points hand"}]}`
+
+use crate::evaluate::{Evaluated, Wait};
+use crate::groups::{Call, Hand, OpenSet, Set, Source, TilesNewType};
+use crate::parse::Node;
+use crate::score::{Fu, Han, Score};
+use crate::tiles::{Dragon, Honour, Tile, Wind};
+use crate::yaku;
+
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+/// 牌の並びをMPSZ表記の文字列として扱う
+mod mpsz {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(tiles: &[Tile], serializer: S) -> Result<S::Ok, S::Error> {
+        TilesNewType(tiles.to_vec()).to_mpsz(&mut Vec::new()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Tile>, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let TilesNewType(tiles) = TilesNewType::from_str(&string).map_err(DeError::custom)?;
+        Ok(tiles)
+    }
+}
+
+impl Serialize for Tile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.mpsz().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let TilesNewType(tiles) = TilesNewType::from_str(&string).map_err(DeError::custom)?;
+        match tiles.as_slice() {
+            [tile] => Ok(tile.clone()),
+            _ => Err(DeError::custom(format!("牌が1枚ではありません: {}", string))),
+        }
+    }
+}
+
+impl Serialize for Honour {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Tile::Honour(self.clone()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Honour {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Tile::deserialize(deserializer)? {
+            Tile::Honour(honour) => Ok(honour),
+            tile => Err(DeError::custom(format!("字牌ではありません: {}", tile.mpsz()))),
+        }
+    }
+}
+
+impl Serialize for Wind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.clone().tile().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Wind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Honour::deserialize(deserializer)? {
+            Honour::Wind(wind) => Ok(wind),
+            honour => Err(DeError::custom(format!("風牌ではありません: {}", Tile::Honour(honour).mpsz()))),
+        }
+    }
+}
+
+impl Serialize for Dragon {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.clone().tile().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dragon {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Honour::deserialize(deserializer)? {
+            Honour::Dragon(dragon) => Ok(dragon),
+            honour => Err(DeError::custom(format!("三元牌ではありません: {}", Tile::Honour(honour).mpsz()))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct HandRepr {
+    #[serde(with = "mpsz")]
+    tiles: Vec<Tile>,
+    open_sets: Vec<OpenSet>,
+    winning: Tile,
+    draw: Option<bool>,
+    #[serde(with = "mpsz")]
+    red_fives: Vec<Tile>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    flowers: u32,
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
+}
+
+impl Serialize for Hand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HandRepr {
+            tiles: self.tiles.clone(),
+            open_sets: self.open_sets.clone(),
+            winning: self.winning.clone(),
+            draw: self.draw,
+            red_fives: self.red_fives.clone(),
+            flowers: self.flowers,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let HandRepr { mut tiles, open_sets, winning, draw, mut red_fives, flowers } = HandRepr::deserialize(deserializer)?;
+        tiles.sort();
+        red_fives.sort();
+        Ok(Hand { tiles, open_sets, winning, draw, red_fives, flowers })
+    }
+}
+
+impl Serialize for Source {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self {
+            Source::Left => "left",
+            Source::Opposite => "opposite",
+            Source::Right => "right",
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Source {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        match string.as_str() {
+            "left" => Ok(Source::Left),
+            "opposite" => Ok(Source::Opposite),
+            "right" => Ok(Source::Right),
+            _ => Err(DeError::custom(format!("鳴いた相手が不正です: {}", string))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CallRepr {
+    tile: Tile,
+    source: Source,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OpenSetType {
+    Chow,
+    Pung,
+    Kong,
+    AddedKong,
+    ConcealedKong,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OpenSetRepr {
+    #[serde(rename = "type")]
+    kind: OpenSetType,
+    #[serde(with = "mpsz")]
+    tiles: Vec<Tile>,
+    call: Option<CallRepr>,
+}
+
+impl Serialize for OpenSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (kind, tiles) = match &self {
+            OpenSet::Chow(tiles, _) => (OpenSetType::Chow, tiles),
+            OpenSet::Pung(tiles, _) => (OpenSetType::Pung, tiles),
+            OpenSet::Kong(tiles, _) => (OpenSetType::Kong, tiles),
+            OpenSet::AddedKong(tiles, _) => (OpenSetType::AddedKong, tiles),
+            OpenSet::ConcealedKong(tiles) => (OpenSetType::ConcealedKong, tiles),
+        };
+        let call = self.call().map(|call| CallRepr { tile: call.tile.clone(), source: call.source });
+        OpenSetRepr { kind, tiles: tiles.clone(), call }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OpenSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let OpenSetRepr { kind, mut tiles, call } = OpenSetRepr::deserialize(deserializer)?;
+        tiles.sort();
+        let call = call.map(|CallRepr { tile, source }| Call::new(tile, source));
+        Ok(match kind {
+            OpenSetType::Chow => OpenSet::Chow(tiles, call),
+            OpenSetType::Pung => OpenSet::Pung(tiles, call),
+            OpenSetType::Kong => OpenSet::Kong(tiles, call),
+            OpenSetType::AddedKong => OpenSet::AddedKong(tiles, call),
+            OpenSetType::ConcealedKong => OpenSet::ConcealedKong(tiles),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SetType {
+    Chow,
+    Pung,
+    Pair,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SetRepr {
+    #[serde(rename = "type")]
+    kind: SetType,
+    #[serde(with = "mpsz")]
+    tiles: Vec<Tile>,
+}
+
+impl Serialize for Set {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (kind, tiles) = match &self {
+            Set::Chow(tiles) => (SetType::Chow, tiles),
+            Set::Pung(tiles) => (SetType::Pung, tiles),
+            Set::Pair(tiles) => (SetType::Pair, tiles),
+        };
+        SetRepr { kind, tiles: tiles.clone() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Set {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let SetRepr { kind, tiles } = SetRepr::deserialize(deserializer)?;
+        Ok(match kind {
+            SetType::Chow => Set::Chow(tiles),
+            SetType::Pung => Set::Pung(tiles),
+            SetType::Pair => Set::Pair(tiles),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct NodeRepr {
+    sets: Vec<Set>,
+    open_sets: Vec<OpenSet>,
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        NodeRepr { sets: self.sets.clone(), open_sets: self.open_sets.clone() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let NodeRepr { sets, open_sets } = NodeRepr::deserialize(deserializer)?;
+        Ok(Node { remaining: Vec::new(), open_sets, sets, pong: Box::new(None), chow: Box::new(None) })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum WaitType {
+    Ryanmen,
+    Kanchan,
+    Penchan,
+    Tanki,
+    Shanpon,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WaitRepr {
+    #[serde(rename = "type")]
+    kind: WaitType,
+    fu: Fu,
+    winning: Tile,
+    node: Node,
+}
+
+impl Serialize for Wait {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (kind, node, fu, winning) = match &self {
+            Wait::Ryanmen(node, fu, winning) => (WaitType::Ryanmen, node, fu, winning),
+            Wait::Kanchan(node, fu, winning) => (WaitType::Kanchan, node, fu, winning),
+            Wait::Penchan(node, fu, winning) => (WaitType::Penchan, node, fu, winning),
+            Wait::Tanki(node, fu, winning) => (WaitType::Tanki, node, fu, winning),
+            Wait::Shanpon(node, fu, winning) => (WaitType::Shanpon, node, fu, winning),
+        };
+        WaitRepr { kind, fu: *fu, winning: winning.clone(), node: node.clone() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Wait {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let WaitRepr { kind, fu, winning, node } = WaitRepr::deserialize(deserializer)?;
+        Ok(match kind {
+            WaitType::Ryanmen => Wait::Ryanmen(node, fu, winning),
+            WaitType::Kanchan => Wait::Kanchan(node, fu, winning),
+            WaitType::Penchan => Wait::Penchan(node, fu, winning),
+            WaitType::Tanki => Wait::Tanki(node, fu, winning),
+            WaitType::Shanpon => Wait::Shanpon(node, fu, winning),
+        })
+    }
+}
+
+impl Serialize for Han {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Han {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Han(u32::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for Fu {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Fu {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Fu(u32::deserialize(deserializer)?))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Limit {
+    Mangan,
+    Haneman,
+    Baiman,
+    Sanbaiman,
+    Yakuman,
+    KazoeYakuman,
+    MultipleYakuman,
+}
+
+#[derive(Serialize)]
+struct TsumoRepr {
+    non_dealer: u32,
+    dealer: u32,
+}
+
+#[derive(Serialize)]
+struct PaymentRepr {
+    ron: u32,
+    tsumo: TsumoRepr,
+}
+
+impl PaymentRepr {
+    fn new(score: &Score, is_dealer: bool) -> Self {
+        let (non_dealer, dealer) = score.tsumo_payment(is_dealer);
+        PaymentRepr { ron: score.ron_payment(is_dealer), tsumo: TsumoRepr { non_dealer, dealer } }
+    }
+}
+
+#[derive(Serialize)]
+struct PaymentsRepr {
+    non_dealer: PaymentRepr,
+    dealer: PaymentRepr,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScoreRepr {
+    limit: Option<Limit>,
+    han: Han,
+    fu: Option<Fu>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    multiple: Option<u8>,
+    #[serde(skip_deserializing)]
+    payments: Option<PaymentsRepr>,
+}
+
+impl Serialize for Score {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (limit, fu, multiple) = match &self {
+            Score::Mangan { .. } => (Some(Limit::Mangan), None, None),
+            Score::Haneman { .. } => (Some(Limit::Haneman), None, None),
+            Score::Baiman { .. } => (Some(Limit::Baiman), None, None),
+            Score::Sanbaiman { .. } => (Some(Limit::Sanbaiman), None, None),
+            Score::Yakuman => (Some(Limit::Yakuman), None, Some(1)),
+            Score::KazoeYakuman { .. } => (Some(Limit::KazoeYakuman), None, None),
+            Score::MultipleYakuman { multiple } => (Some(Limit::MultipleYakuman), None, Some(*multiple)),
+            Score::Other { fu, .. } => (None, Some(*fu), None),
+        };
+        let payments = PaymentsRepr { non_dealer: PaymentRepr::new(self, false), dealer: PaymentRepr::new(self, true) };
+        ScoreRepr { limit, han: self.han(), fu, multiple, payments: Some(payments) }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Score {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ScoreRepr { limit, han, fu, multiple, .. } = ScoreRepr::deserialize(deserializer)?;
+        Ok(match limit {
+            Some(Limit::Mangan) => Score::Mangan { han },
+            Some(Limit::Haneman) => Score::Haneman { han },
+            Some(Limit::Baiman) => Score::Baiman { han },
+            Some(Limit::Sanbaiman) => Score::Sanbaiman { han },
+            Some(Limit::KazoeYakuman) => Score::KazoeYakuman { han },
+            Some(Limit::Yakuman) | Some(Limit::MultipleYakuman) => Score::yakuman(multiple.unwrap_or(1)),
+            None => match fu {
+                Some(fu) => Score::Other { han, fu },
+                None => return Err(DeError::missing_field("fu")),
+            },
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct YakuRepr {
+    #[serde(default, skip_deserializing)]
+    id: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EvaluatedRepr {
+    node: Node,
+    score: Score,
+    fu: Fu,
+    yaku: Vec<YakuRepr>,
+}
+
+impl Serialize for Evaluated {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let yaku = self.yaku_list().iter()
+            .map(|name| YakuRepr { id: yaku::id(name), name: name.clone() })
+            .collect();
+        EvaluatedRepr { node: self.node().clone(), score: *self.score(), fu: self.fu(), yaku }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Evaluated {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let EvaluatedRepr { node, score, fu, yaku } = EvaluatedRepr::deserialize(deserializer)?;
+        Ok(Evaluated::new(node, score, fu, yaku.into_iter().map(|yaku| yaku.name).collect()))
+    }
+}
@@ -0,0 +1,171 @@
+use crate::groups::{OpenSet, Sets};
+use crate::tiles::Tile;
+
+/// 向聴数を数える
+///
+/// 和了形は-1、聴牌は0となる。一般形、七対子、国士無双のうち最も小さい値を返す
+/// (七対子と国士無双は鳴いていない場合のみ)
+pub fn shanten(tiles: &[Tile], open_sets: &[OpenSet]) -> i32 {
+    let mut counts = counts(tiles);
+    let regular = regular(&mut counts, open_sets.len() as i32);
+    if !open_sets.is_empty() {
+        return regular;
+    }
+    regular.min(seven_pairs(&counts)).min(thirteen_orphans(&counts))
+}
+
+/// 有効牌 (引くと向聴数が下がる牌と残り枚数)
+///
+/// 残り枚数は手牌と晒した面子から見えている枚数を除いたもの
+pub fn acceptance(tiles: &[Tile], open_sets: &[OpenSet]) -> Vec<(Tile, u8)> {
+    let current = shanten(tiles, open_sets);
+    let visible = visible_counts(tiles, open_sets);
+    let mut tiles = tiles.to_vec();
+    Tile::all().into_iter().filter_map(|tile| {
+        let remaining = 4u8.saturating_sub(visible[tile.index()]);
+        if remaining == 0 {
+            return None;
+        }
+        tiles.push(tile.clone());
+        let improved = shanten(&tiles, open_sets) < current;
+        tiles.pop();
+        if improved { Some((tile, remaining)) } else { None }
+    }).collect()
+}
+
+/// 打牌候補
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discard {
+    /// 打牌
+    pub tile: Tile,
+    /// 打牌後の向聴数
+    pub shanten: i32,
+    /// 打牌後の有効牌
+    pub acceptance: Vec<(Tile, u8)>,
+}
+
+impl Discard {
+    /// 有効牌の残り枚数の合計
+    pub fn count(&self) -> u32 {
+        self.acceptance.iter().map(|(_, remaining)| *remaining as u32).sum()
+    }
+}
+
+/// 打牌候補を向聴数が小さい順、有効牌が多い順に並べる
+pub fn discards(tiles: &[Tile], open_sets: &[OpenSet]) -> Vec<Discard> {
+    let mut candidates: Vec<Tile> = tiles.to_vec();
+    candidates.sort();
+    candidates.dedup();
+    let mut discards: Vec<Discard> = candidates.into_iter().map(|tile| {
+        let mut remaining = tiles.to_vec();
+        let i = remaining.iter().position(|t| t == &tile).unwrap();
+        remaining.remove(i);
+        Discard { shanten: shanten(&remaining, open_sets), acceptance: acceptance(&remaining, open_sets), tile }
+    }).collect();
+    discards.sort_by(|a, b| a.shanten.cmp(&b.shanten).then(b.count().cmp(&a.count())).then(a.tile.cmp(&b.tile)));
+    discards
+}
+
+/// 種類ごとの枚数
+fn counts(tiles: &[Tile]) -> [u8; 34] {
+    let mut counts = [0; 34];
+    tiles.iter().for_each(|tile| counts[tile.index()] += 1);
+    counts
+}
+
+/// 手牌と晒した面子で見えている枚数
+fn visible_counts(tiles: &[Tile], open_sets: &[OpenSet]) -> [u8; 34] {
+    let mut counts = counts(tiles);
+    open_sets.iter().for_each(|open_set| open_set.vec().iter().for_each(|tile| counts[tile.index()] += 1));
+    counts
+}
+
+/// 一般形の向聴数
+fn regular(counts: &mut [u8; 34], melds: i32) -> i32 {
+    let mut best = search(counts, 0, melds, 0, 0);
+    for i in 0..34 {
+        if counts[i] >= 2 {
+            counts[i] -= 2;
+            best = best.min(search(counts, 0, melds, 0, 1));
+            counts[i] += 2;
+        }
+    }
+    best
+}
+
+/// 面子と塔子を取り出して向聴数の最小値を探す
+fn search(counts: &mut [u8; 34], i: usize, sets: i32, partials: i32, head: i32) -> i32 {
+    let mut i = i;
+    while i < 34 && counts[i] == 0 {
+        i += 1;
+    }
+    if i == 34 {
+        // 面子と塔子の合計は4まで
+        return 8 - 2 * sets - partials.min(4 - sets) - head;
+    }
+    let suits = i < 27;
+    let position = i % 9;
+    let mut best = 8;
+
+    // 刻子
+    if counts[i] >= 3 {
+        counts[i] -= 3;
+        best = best.min(search(counts, i, sets + 1, partials, head));
+        counts[i] += 3;
+    }
+    // 順子
+    if suits && position <= 6 && counts[i + 1] > 0 && counts[i + 2] > 0 {
+        take(counts, &[i, i + 1, i + 2]);
+        best = best.min(search(counts, i, sets + 1, partials, head));
+        give(counts, &[i, i + 1, i + 2]);
+    }
+    if sets + partials < 4 {
+        // 対子
+        if counts[i] >= 2 {
+            counts[i] -= 2;
+            best = best.min(search(counts, i, sets, partials + 1, head));
+            counts[i] += 2;
+        }
+        // 両面・辺張
+        if suits && position <= 7 && counts[i + 1] > 0 {
+            take(counts, &[i, i + 1]);
+            best = best.min(search(counts, i, sets, partials + 1, head));
+            give(counts, &[i, i + 1]);
+        }
+        // 嵌張
+        if suits && position <= 6 && counts[i + 2] > 0 {
+            take(counts, &[i, i + 2]);
+            best = best.min(search(counts, i, sets, partials + 1, head));
+            give(counts, &[i, i + 2]);
+        }
+    }
+    // 孤立牌として扱う
+    let count = counts[i];
+    counts[i] = 0;
+    best = best.min(search(counts, i + 1, sets, partials, head));
+    counts[i] = count;
+    best
+}
+
+fn take(counts: &mut [u8; 34], indices: &[usize]) {
+    indices.iter().for_each(|i| counts[*i] -= 1);
+}
+
+fn give(counts: &mut [u8; 34], indices: &[usize]) {
+    indices.iter().for_each(|i| counts[*i] += 1);
+}
+
+/// 七対子の向聴数 (同じ牌4枚は2組の対子とみなさない)
+fn seven_pairs(counts: &[u8; 34]) -> i32 {
+    let pairs = counts.iter().filter(|count| **count >= 2).count() as i32;
+    let kinds = counts.iter().filter(|count| **count >= 1).count() as i32;
+    6 - pairs + (7 - kinds).max(0)
+}
+
+/// 国士無双の向聴数
+fn thirteen_orphans(counts: &[u8; 34]) -> i32 {
+    let yaotyu: Vec<u8> = Tile::all().iter().filter(|tile| tile.is_yaotyu()).map(|tile| counts[tile.index()]).collect();
+    let kinds = yaotyu.iter().filter(|count| **count >= 1).count() as i32;
+    let pair = if yaotyu.iter().any(|count| *count >= 2) { 1 } else { 0 };
+    13 - kinds - pair
+}
@@ -0,0 +1,162 @@
+//! エージェント同士の対局を繰り返して成績を集計する
+
+use crate::agent::{Agent, Observation};
+use crate::game::{Match, MatchConfig};
+use crate::record::Record;
+use crate::round::RoundResult;
+
+/// エージェントごとの成績
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentStats {
+    /// エージェントの名前と並びでの番号 (`baseline#1`など、同じエージェント同士でも区別する)
+    pub name: String,
+    /// 対局数
+    pub games: usize,
+    /// 局数
+    pub rounds: usize,
+    /// 和了した局数
+    pub wins: usize,
+    /// 放銃した局数
+    pub deal_ins: usize,
+    /// 順位の合計
+    pub placements: usize,
+    /// ポイントの合計
+    pub score: f64,
+}
+
+impl AgentStats {
+    fn new(name: String) -> Self {
+        AgentStats { name, games: 0, rounds: 0, wins: 0, deal_ins: 0, placements: 0, score: 0.0 }
+    }
+
+    /// 和了率
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins, self.rounds)
+    }
+
+    /// 放銃率
+    pub fn deal_in_rate(&self) -> f64 {
+        ratio(self.deal_ins, self.rounds)
+    }
+
+    /// 平均順位
+    pub fn average_placement(&self) -> f64 {
+        ratio(self.placements, self.games)
+    }
+
+    /// 平均ポイント
+    pub fn average_score(&self) -> f64 {
+        if self.games == 0 { 0.0 } else { self.score / self.games as f64 }
+    }
+}
+
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { count as f64 / total as f64 }
+}
+
+/// 1対局の席ごとの結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    /// 局数
+    pub rounds: usize,
+    /// 各席の和了した局数
+    pub wins: Vec<usize>,
+    /// 各席の放銃した局数
+    pub deal_ins: Vec<usize>,
+    /// 各席の順位
+    pub placements: Vec<usize>,
+    /// 各席のポイント
+    pub scores: Vec<f64>,
+    /// 全ての局の牌譜
+    pub record: Record,
+}
+
+/// 対局の繰り返し
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    config: MatchConfig,
+}
+
+impl Simulator {
+    pub fn new(config: MatchConfig) -> Self {
+        Simulator { config }
+    }
+
+    pub fn config(&self) -> &MatchConfig {
+        &self.config
+    }
+
+    /// 1対局を行う (`agents[i]`が席iに座る)
+    pub fn play(&self, agents: &mut [&mut dyn Agent], seed: u64) -> Result<GameResult, failure::Error> {
+        let players = self.config.players;
+        if agents.len() != players {
+            return Err(format_err!("エージェントの数が人数と一致しません: {}人", agents.len()));
+        }
+        let mut game = Match::new(self.config.clone(), seed);
+        let mut result = GameResult {
+            rounds: 0,
+            wins: vec![0; players],
+            deal_ins: vec![0; players],
+            placements: vec![0; players],
+            scores: vec![0.0; players],
+            record: Record::new(),
+        };
+        while !game.is_finished() {
+            let mut round = game.next_round()?;
+            while !round.is_finished() {
+                let seat = round.waiting()[0];
+                let action = agents[seat].act(&Observation::new(&round, seat));
+                round.apply(seat, action).map_err(|error| format_err!("{}の{}", agents[seat].name(), error))?;
+            }
+            if let Some(RoundResult::Win { wins, .. }) = round.result() {
+                wins.iter().for_each(|win| result.wins[win.seat] += 1);
+                if let Some(from) = wins[0].from {
+                    result.deal_ins[from] += 1;
+                }
+            }
+            result.rounds += 1;
+            result.record.push(&round);
+            game.finish_round(&round)?;
+        }
+        for standing in game.standings() {
+            result.placements[standing.seat] = standing.rank;
+            result.scores[standing.seat] = standing.score;
+        }
+        Ok(result)
+    }
+
+    /// `games`回対局して成績を集計する
+    ///
+    /// シードは`seed`から順に使い、対局ごとに座る席を1つずつずらす
+    pub fn run(&self, agents: &mut [Box<dyn Agent>], games: usize, seed: u64) -> Result<Vec<AgentStats>, failure::Error> {
+        let players = agents.len();
+        let mut stats: Vec<AgentStats> = agents.iter().enumerate()
+            .map(|(i, agent)| AgentStats::new(format!("{}#{}", agent.name(), i + 1)))
+            .collect();
+        for game in 0..games {
+            let mut seated: Vec<&mut dyn Agent> = agents.iter_mut().map(|agent| agent.as_mut() as &mut dyn Agent).collect();
+            seated.rotate_left(game % players.max(1));
+            let result = self.play(&mut seated, seed.wrapping_add(game as u64))?;
+            for seat in 0..players {
+                let stats = &mut stats[(seat + game) % players];
+                stats.games += 1;
+                stats.rounds += result.rounds;
+                stats.wins += result.wins[seat];
+                stats.deal_ins += result.deal_ins[seat];
+                stats.placements += result.placements[seat];
+                stats.score += result.scores[seat];
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// 成績表
+pub fn stats_table(stats: &[AgentStats]) -> String {
+    let mut table = "エージェント  対局  和了率  放銃率  平均順位  平均ポイント\n".to_string();
+    for stats in stats {
+        table.push_str(&format!("{:<12}  {:>4}  {:>5.1}%  {:>5.1}%  {:>8.2}  {:>+12.1}\n", stats.name, stats.games,
+                                stats.win_rate() * 100.0, stats.deal_in_rate() * 100.0, stats.average_placement(), stats.average_score()));
+    }
+    table
+}
@@ -0,0 +1,214 @@
+use crate::face::{face, Face};
+use crate::groups::{Hand, OpenSet, Source};
+use crate::tiles::{Dragon, Honour, Tile, Wind};
+
+/// 牌の幅
+const TILE_WIDTH: u32 = 30;
+/// 牌の高さ
+const TILE_HEIGHT: u32 = 40;
+/// 余白
+const MARGIN: u32 = 10;
+/// 当たり牌や面子の前の間隔
+const GAP: u32 = 12;
+
+/// 手牌をSVGとして描画する
+///
+/// 手牌、当たり牌(間隔を空ける)、鳴いた面子の順に並べる。鳴いた牌は出所に応じて横向きに、
+/// 加槓は横向きの牌を重ね、暗槓は両端を裏向きに描画する
+pub struct SvgRenderer {
+    /// ドラ表示牌
+    dora_indicators: Vec<Tile>,
+}
+
+impl SvgRenderer {
+    pub fn new(dora_indicators: Vec<Tile>) -> Self {
+        SvgRenderer { dora_indicators }
+    }
+
+    pub fn render(&self, hand: &Hand) -> String {
+        let mut red_fives = hand.red_fives.clone();
+        let mut elements = Vec::new();
+        let mut x = MARGIN;
+
+        // ドラ表示牌
+        let dora_row = if self.dora_indicators.is_empty() { 0 } else { TILE_HEIGHT + GAP };
+        if !self.dora_indicators.is_empty() {
+            elements.push(format!("<text x=\"{}\" y=\"{}\" font-size=\"12\" dominant-baseline=\"middle\">ドラ表示牌</text>",
+                                  MARGIN, MARGIN + TILE_HEIGHT / 2));
+            let mut dora_x = MARGIN + 70;
+            for tile in &self.dora_indicators {
+                elements.push(upright(dora_x, MARGIN, &Face::Up(tile.clone(), false)));
+                dora_x += TILE_WIDTH;
+            }
+        }
+
+        // 横向きの牌を2枚重ねられるように、牌の下端を揃える
+        let bottom = MARGIN + dora_row + TILE_WIDTH * 2;
+        let top = bottom - TILE_HEIGHT;
+
+        // 手牌 (当たり牌を除く)
+        let mut tiles = hand.tiles.clone();
+        if let Some(i) = tiles.iter().position(|tile| tile == &hand.winning) {
+            tiles.remove(i);
+        }
+        for tile in &tiles {
+            elements.push(upright(x, top, &face(tile, &mut red_fives)));
+            x += TILE_WIDTH;
+        }
+
+        // 鳴いた面子
+        let mut melds = Vec::new();
+        let mut meld_x = 0;
+        for open_set in &hand.open_sets {
+            meld_x += GAP;
+            let (mut meld, width) = meld(meld_x, bottom, open_set, &mut red_fives);
+            melds.append(&mut meld);
+            meld_x += width;
+        }
+
+        // 当たり牌
+        x += GAP;
+        elements.push(upright(x, top, &face(&hand.winning, &mut red_fives)));
+        x += TILE_WIDTH;
+
+        elements.push(format!("<g transform=\"translate({},0)\">{}</g>", x, melds.join("")));
+        x += meld_x;
+
+        let (width, height) = (x + MARGIN, bottom + MARGIN);
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\">{}</svg>",
+                width, height, width, height, elements.join(""))
+    }
+}
+
+/// 鳴いた面子を描画する (要素と幅を返す)
+fn meld(x: u32, bottom: u32, open_set: &OpenSet, red_fives: &mut Vec<Tile>) -> (Vec<String>, u32) {
+    let top = bottom - TILE_HEIGHT;
+    let mut elements = Vec::new();
+    let mut x = x;
+    let start = x;
+
+    if let OpenSet::ConcealedKong(tiles) = open_set {
+        // 両端を裏向きにする
+        for (i, tile) in tiles.iter().enumerate() {
+            let face = if i == 0 || i == tiles.len() - 1 { Face::Down } else { face(tile, red_fives) };
+            elements.push(upright(x, top, &face));
+            x += TILE_WIDTH;
+        }
+        return (elements, x - start);
+    }
+
+    let tiles = match open_set {
+        OpenSet::Pung(tiles, _) | OpenSet::Chow(tiles, _) | OpenSet::Kong(tiles, _) | OpenSet::AddedKong(tiles, _) => tiles,
+        OpenSet::ConcealedKong(_) => unreachable!(),
+    };
+    let call = match open_set.call() {
+        Some(call) => call,
+        None => {
+            // 鳴いた牌が分からない場合は全て縦向き
+            for tile in tiles {
+                elements.push(upright(x, top, &face(tile, red_fives)));
+                x += TILE_WIDTH;
+            }
+            return (elements, x - start);
+        }
+    };
+
+    // 鳴いた牌以外 (加槓の場合は加えた牌も除く)
+    let mut others = tiles.clone();
+    if let Some(i) = others.iter().position(|tile| tile == &call.tile) {
+        others.remove(i);
+    }
+    let added = match open_set {
+        OpenSet::AddedKong(..) => others.pop(),
+        _ => None,
+    };
+
+    // 鳴いた牌の位置 (上家: 左端, 対面: 左から2枚目, 下家: 右端)
+    let position = match call.source {
+        Source::Left => 0,
+        Source::Opposite => 1,
+        Source::Right => others.len(),
+    };
+    let called_face = face(&call.tile, red_fives);
+    for i in 0..=others.len() {
+        if i == position {
+            elements.push(sideways(x, bottom, &called_face, 0));
+            if let Some(added) = &added {
+                elements.push(sideways(x, bottom, &face(added, red_fives), 1));
+            }
+            x += TILE_HEIGHT;
+        }
+        if let Some(tile) = others.get(i) {
+            elements.push(upright(x, top, &face(tile, red_fives)));
+            x += TILE_WIDTH;
+        }
+    }
+    (elements, x - start)
+}
+
+/// 縦向きの牌
+fn upright(x: u32, y: u32, face: &Face) -> String {
+    tile_body(x, y, face, None)
+}
+
+/// 横向きの牌 (`level`枚目として下端から積む)
+fn sideways(x: u32, bottom: u32, face: &Face, level: u32) -> String {
+    let cx = x + TILE_HEIGHT / 2;
+    let cy = bottom - TILE_WIDTH / 2 - TILE_WIDTH * level;
+    tile_body(cx - TILE_WIDTH / 2, cy - TILE_HEIGHT / 2, face, Some((cx, cy)))
+}
+
+/// 牌を描画する (`rotation`が指定された場合はその点を中心に90度回転する)
+fn tile_body(x: u32, y: u32, face: &Face, rotation: Option<(u32, u32)>) -> String {
+    let transform = match rotation {
+        Some((cx, cy)) => format!(" transform=\"rotate(-90 {} {})\"", cx, cy),
+        None => String::new(),
+    };
+    let body = match face {
+        Face::Down => format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"#2e7d32\" stroke=\"#333\"/>",
+                              x, y, TILE_WIDTH, TILE_HEIGHT),
+        Face::Up(tile, red) => {
+            let (label, colour) = label(tile, *red);
+            let cx = x + TILE_WIDTH / 2;
+            let mut body = format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"#fdfbf3\" stroke=\"#333\"/>",
+                                   x, y, TILE_WIDTH, TILE_HEIGHT);
+            match label {
+                (Some(number), suit) => {
+                    body.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"16\" font-weight=\"bold\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+                                           cx, y + 18, colour, number));
+                    body.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+                                           cx, y + 34, colour, suit));
+                }
+                (None, honour) => {
+                    body.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"20\" font-weight=\"bold\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+                                           cx, y + 28, colour, honour));
+                }
+            }
+            body
+        }
+    };
+    format!("<g{}>{}</g>", transform, body)
+}
+
+/// 牌の文字と色 ((数字, 種類の文字), 色)
+fn label(tile: &Tile, red: bool) -> ((Option<u8>, char), &'static str) {
+    let (label, colour) = match tile {
+        Tile::Character(u) => ((Some(*u), '萬'), "#222"),
+        Tile::Circle(u) => ((Some(*u), '筒'), "#1f4e9c"),
+        Tile::Bamboo(u) => ((Some(*u), '索'), "#1b7a3a"),
+        Tile::Honour(Honour::Wind(wind)) => ((None, match wind {
+            Wind::East => '東',
+            Wind::South => '南',
+            Wind::West => '西',
+            Wind::North => '北',
+        }), "#222"),
+        Tile::Honour(Honour::Dragon(Dragon::White)) => ((None, '白'), "#8a8a8a"),
+        Tile::Honour(Honour::Dragon(Dragon::Green)) => ((None, '發'), "#1b7a3a"),
+        Tile::Honour(Honour::Dragon(Dragon::Red)) => ((None, '中'), "#c62828"),
+    };
+    if red {
+        (label, "#d32f2f")
+    } else {
+        (label, colour)
+    }
+}
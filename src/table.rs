@@ -0,0 +1,157 @@
+use crate::output::csv_field;
+use crate::score::{Fu, Han, Score};
+
+/// 点数表に載せる符 (20符、25符、30符から110符まで)
+pub fn fu_values() -> Vec<Fu> {
+    let mut fu_values = vec![Fu(20), Fu(25)];
+    (3..=11).for_each(|fu| fu_values.push(Fu(fu * 10)));
+    fu_values
+}
+
+/// 複合役満の上限 (天和、大四喜、字一色、四暗刻単騎の複合)
+pub const MAX_YAKUMAN: u8 = 6;
+
+/// 満貫以上の役の一覧 (最低翻数で代表する、役満は`MAX_YAKUMAN`倍まで)
+pub fn limits() -> Vec<Score> {
+    let mut limits = vec![Score::new(Han(5), Fu(0)), Score::new(Han(6), Fu(0)), Score::new(Han(8), Fu(0)),
+                          Score::new(Han(11), Fu(0))];
+    (1..=MAX_YAKUMAN).for_each(|multiple| limits.push(Score::yakuman(multiple)));
+    limits
+}
+
+/// 満貫以上の役の名前 (4倍以上の役満は倍数を付ける)
+pub fn limit_name(score: &Score) -> String {
+    match score {
+        Score::MultipleYakuman { multiple } if *multiple > 3 => format!("{}倍役満", multiple),
+        _ => score.jp_name(),
+    }
+}
+
+/// 翻数と符の組み合わせが和了形として成立するか
+///
+/// 20符は平和ツモ(2翻以上)、25符は七対子(2翻以上、ツモは3翻以上)のみ
+pub fn is_possible(han: Han, fu: Fu, draw: bool) -> bool {
+    let (Han(han), Fu(fu)) = (han, fu);
+    match fu {
+        20 => draw && han >= 2,
+        25 => han >= 2 && !(draw && han < 3),
+        _ => han >= 1,
+    }
+}
+
+/// 点数表の項目
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// 翻数 (満貫以上は最低翻数)
+    pub han: Han,
+    /// 符 (満貫以上は`None`)
+    pub fu: Option<Fu>,
+    pub score: Score,
+}
+
+/// 和了者が受け取る点数(ツモは合計)が`amount`になる翻数と符、満貫以上の役を返す
+pub fn lookup(amount: u32, is_dealer: bool, draw: bool, kiriage: bool) -> Vec<Entry> {
+    entries(draw, kiriage).into_iter().filter(|entry| entry.score.points(is_dealer, draw) == amount).collect()
+}
+
+/// ツモ和了の支払い(子の支払い, 親の支払い)が一致する翻数と符、満貫以上の役を返す
+///
+/// 和了者が親の場合、親の支払いは0とする
+pub fn lookup_tsumo(payment: (u32, u32), is_dealer: bool, kiriage: bool) -> Vec<Entry> {
+    entries(true, kiriage).into_iter().filter(|entry| entry.score.tsumo_payment(is_dealer) == payment).collect()
+}
+
+/// 全ての翻数と符(4翻まで)と満貫以上の役
+fn entries(draw: bool, kiriage: bool) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for han in 1..=4 {
+        for fu in fu_values() {
+            if is_possible(Han(han), fu, draw) {
+                entries.push(Entry { han: Han(han), fu: Some(fu), score: Score::new_with_kiriage(Han(han), fu, kiriage) });
+            }
+        }
+    }
+    limits().into_iter().for_each(|score| entries.push(Entry { han: score.han(), fu: None, score }));
+    entries
+}
+
+/// 翻数×符の点数表
+///
+/// 各項目は`ロン / ツモ`(`3900 / 1000-2000`、親のツモは`3900 / 1300all`)で、成立しない場合は`-`とする
+pub struct ScoreTable {
+    /// 見出し
+    header: Vec<String>,
+    /// 行 (先頭は符または役の名前)
+    rows: Vec<Vec<String>>,
+}
+
+impl ScoreTable {
+    pub fn new(is_dealer: bool, kiriage: bool) -> Self {
+        let mut header = vec!["符".to_string()];
+        (1..=4).for_each(|han| header.push(Han(han).to_string()));
+
+        let cell = |score: &Score, ron: bool, tsumo: bool| {
+            let ron = if ron { score.ron_payment(is_dealer).to_string() } else { "-".to_string() };
+            let tsumo = match (tsumo, score.tsumo_payment(is_dealer)) {
+                (false, _) => "-".to_string(),
+                (true, (each, _)) if is_dealer => format!("{}all", each),
+                (true, (non_dealer, dealer)) => format!("{}-{}", non_dealer, dealer),
+            };
+            format!("{} / {}", ron, tsumo)
+        };
+
+        let mut rows = Vec::new();
+        for fu in fu_values() {
+            let mut row = vec![fu.to_string()];
+            for han in 1..=4 {
+                let (ron, tsumo) = (is_possible(Han(han), fu, false), is_possible(Han(han), fu, true));
+                row.push(if ron || tsumo { cell(&Score::new_with_kiriage(Han(han), fu, kiriage), ron, tsumo) } else { "-".to_string() });
+            }
+            rows.push(row);
+        }
+        for score in limits() {
+            let mut row = vec![limit_name(&score), cell(&score, true, true)];
+            (2..=4).for_each(|_| row.push(String::new()));
+            rows.push(row);
+        }
+        ScoreTable { header, rows }
+    }
+
+    /// 列を揃えたテキスト
+    pub fn to_text(&self) -> String {
+        let mut widths = vec![0; self.header.len()];
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            row.iter().enumerate().for_each(|(i, cell)| widths[i] = widths[i].max(width(cell)));
+        }
+        let mut text = String::new();
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            let cells: Vec<String> = row.iter().enumerate()
+                .map(|(i, cell)| format!("{}{}", " ".repeat(widths[i] - width(cell)), cell))
+                .collect();
+            text.push_str(cells.join("  ").trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// CSV
+    pub fn to_csv(&self) -> String {
+        std::iter::once(&self.header).chain(self.rows.iter())
+            .map(|row| row.iter().map(|cell| csv_field(cell)).collect::<Vec<String>>().join(",") + "\n")
+            .collect()
+    }
+
+    /// Markdownの表
+    pub fn to_markdown(&self) -> String {
+        let line = |row: &Vec<String>| format!("| {} |\n", row.join(" | "));
+        let mut markdown = line(&self.header);
+        markdown.push_str(&format!("|{}\n", "---|".repeat(self.header.len())));
+        self.rows.iter().for_each(|row| markdown.push_str(&line(row)));
+        markdown
+    }
+}
+
+/// 端末での表示幅 (ASCII以外は2桁とみなす)
+fn width(s: &str) -> usize {
+    s.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
@@ -0,0 +1,125 @@
+use crate::face::{face, Face};
+use crate::groups::{Hand, OpenSet, Sets};
+use crate::parse::Node;
+use crate::tiles::{Dragon, Honour, Tile, Wind};
+use std::io::IsTerminal;
+
+/// 牌の絵文字が表示できない端末向けに、牌を罫線の枠で描画する
+///
+/// ```text
+/// ┌──┐┌──┐┌──┐
+/// │1m││2m││3m│
+/// └──┘└──┘└──┘
+/// ```
+///
+/// 色を使う場合は種類ごとに数字を色分けし、赤五を強調する。色を使わない場合、赤五は`0m`のように表示する
+pub struct TerminalRenderer {
+    /// ANSIエスケープシーケンスで色を付けるか
+    colour: bool,
+}
+
+impl TerminalRenderer {
+    pub fn new(colour: bool) -> Self {
+        TerminalRenderer { colour }
+    }
+
+    /// 標準出力が端末で、`NO_COLOR`が設定されておらず`TERM`が`dumb`でなければ色を使う
+    pub fn detect() -> Self {
+        let no_colour = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+        TerminalRenderer::new(!no_colour && !dumb && std::io::stdout().is_terminal())
+    }
+
+    /// 牌を並べて描画する
+    pub fn render_tiles(&self, tiles: &[Tile], red_fives: &[Tile]) -> String {
+        let mut red_fives = red_fives.to_vec();
+        let faces: Vec<Face> = tiles.iter().map(|tile| face(tile, &mut red_fives)).collect();
+        self.join(&[faces])
+    }
+
+    /// 手牌を描画する (手牌、当たり牌、鳴いた面子の順に間隔を空けて並べる)
+    pub fn render_hand(&self, hand: &Hand) -> String {
+        let mut red_fives = hand.red_fives.clone();
+        let mut tiles = hand.tiles.clone();
+        if let Some(i) = tiles.iter().position(|tile| tile == &hand.winning) {
+            tiles.remove(i);
+        }
+        let mut groups = vec![tiles.iter().map(|tile| face(tile, &mut red_fives)).collect::<Vec<Face>>()];
+        let winning = face(&hand.winning, &mut red_fives);
+        hand.open_sets.iter().for_each(|open_set| groups.push(open_faces(open_set, &mut red_fives)));
+        groups.insert(1, vec![winning]);
+        self.join(&groups)
+    }
+
+    /// 面子の分解を描画する (`Display for Node`と同様に、手牌の面子の後に晒した面子を並べる)
+    pub fn render_node(&self, node: &Node, red_fives: &[Tile]) -> String {
+        let mut red_fives = red_fives.to_vec();
+        let mut groups: Vec<Vec<Face>> = node.sets.iter()
+            .map(|set| set.vec().iter().map(|tile| face(tile, &mut red_fives)).collect())
+            .collect();
+        node.open_sets.iter().for_each(|open_set| groups.push(open_faces(open_set, &mut red_fives)));
+        self.join(&groups)
+    }
+
+    /// 牌のまとまりを空白で区切って3行に並べる
+    fn join(&self, groups: &[Vec<Face>]) -> String {
+        let mut lines = [String::new(), String::new(), String::new()];
+        for (i, group) in groups.iter().enumerate() {
+            if i > 0 {
+                lines.iter_mut().for_each(|line| line.push(' '));
+            }
+            for face in group {
+                lines[0].push_str("┌──┐");
+                lines[1].push('│');
+                lines[1].push_str(&self.label(face));
+                lines[1].push('│');
+                lines[2].push_str("└──┘");
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// 枠の中の2桁分の文字
+    fn label(&self, face: &Face) -> String {
+        let (tile, red) = match face {
+            Face::Up(tile, red) => (tile, *red),
+            Face::Down => return "▒▒".to_string(),
+        };
+        let text = match tile {
+            Tile::Honour(Honour::Wind(Wind::East)) => "東".to_string(),
+            Tile::Honour(Honour::Wind(Wind::South)) => "南".to_string(),
+            Tile::Honour(Honour::Wind(Wind::West)) => "西".to_string(),
+            Tile::Honour(Honour::Wind(Wind::North)) => "北".to_string(),
+            Tile::Honour(Honour::Dragon(Dragon::White)) => "白".to_string(),
+            Tile::Honour(Honour::Dragon(Dragon::Green)) => "發".to_string(),
+            Tile::Honour(Honour::Dragon(Dragon::Red)) => "中".to_string(),
+            _ if red && !self.colour => format!("0{}", tile.mpsz_parts().1),
+            _ => tile.mpsz(),
+        };
+        if !self.colour {
+            return text;
+        }
+        let colour = match tile {
+            _ if red => "1;97;41",
+            Tile::Character(_) => "31",
+            Tile::Circle(_) => "34",
+            Tile::Bamboo(_) => "32",
+            Tile::Honour(Honour::Wind(_)) => "1",
+            Tile::Honour(Honour::Dragon(Dragon::White)) => "2",
+            Tile::Honour(Honour::Dragon(Dragon::Green)) => "1;32",
+            Tile::Honour(Honour::Dragon(Dragon::Red)) => "1;31",
+        };
+        format!("\u{1b}[{}m{}\u{1b}[0m", colour, text)
+    }
+}
+
+/// 晒した面子の牌の面 (暗槓は両端を裏向きにする)
+fn open_faces(open_set: &OpenSet, red_fives: &mut Vec<Tile>) -> Vec<Face> {
+    match open_set {
+        OpenSet::ConcealedKong(tiles) => tiles.iter().enumerate()
+            .map(|(i, tile)| if i == 0 || i == tiles.len() - 1 { Face::Down } else { face(tile, red_fives) })
+            .collect(),
+        OpenSet::Pung(tiles, _) | OpenSet::Chow(tiles, _) | OpenSet::Kong(tiles, _) | OpenSet::AddedKong(tiles, _) =>
+            tiles.iter().map(|tile| face(tile, red_fives)).collect(),
+    }
+}
@@ -26,6 +26,19 @@ impl Tile {
         vec!['s', '索']
     }
 
+    /// MPSZ表記の字牌(1z-7z)
+    pub fn honours_markers() -> Vec<char> {
+        vec!['z']
+    }
+
+    /// 数字の後に置いて種類を表す文字か否か
+    pub fn is_suit_marker(c: char) -> bool {
+        Tile::characters_markers().contains(&c)
+            || Tile::circles_markers().contains(&c)
+            || Tile::bamboos_markers().contains(&c)
+            || Tile::honours_markers().contains(&c)
+    }
+
     pub fn east_markers() -> Vec<char> {
         vec!['E', '東']
     }
@@ -54,6 +67,158 @@ impl Tile {
         vec!['T', 'C', '中']
     }
 
+    /// 一文字で表される字牌
+    pub fn from_honour_marker(c: char) -> Option<Tile> {
+        if Tile::east_markers().contains(&c) {
+            Some(Wind::East.tile())
+        } else if Tile::south_markers().contains(&c) {
+            Some(Wind::South.tile())
+        } else if Tile::west_markers().contains(&c) {
+            Some(Wind::West.tile())
+        } else if Tile::north_markers().contains(&c) {
+            Some(Wind::North.tile())
+        } else if Tile::white_markers().contains(&c) {
+            Some(Dragon::White.tile())
+        } else if Tile::green_markers().contains(&c) {
+            Some(Dragon::Green.tile())
+        } else if Tile::red_markers().contains(&c) {
+            Some(Dragon::Red.tile())
+        } else {
+            None
+        }
+    }
+
+    /// 麻雀牌の絵文字(U+1F000-U+1F021)
+    ///
+    /// 花牌・季節牌・ジョーカー・裏向きの牌は`None`
+    pub fn from_glyph(c: char) -> Option<Tile> {
+        let code = c as u32;
+        match code {
+            0x1F000..=0x1F003 => Tile::from_honour_number((code - 0x1F000 + 1) as u8),
+            0x1F004 => Some(Dragon::Red.tile()),
+            0x1F005 => Some(Dragon::Green.tile()),
+            0x1F006 => Some(Dragon::White.tile()),
+            0x1F007..=0x1F00F => Some(Tile::Character((code - 0x1F007 + 1) as u8)),
+            0x1F010..=0x1F018 => Some(Tile::Bamboo((code - 0x1F010 + 1) as u8)),
+            0x1F019..=0x1F021 => Some(Tile::Circle((code - 0x1F019 + 1) as u8)),
+            _ => None,
+        }
+    }
+
+    /// 花牌・季節牌の絵文字(U+1F022-U+1F029)か否か
+    pub fn is_flower_glyph(c: char) -> bool {
+        (0x1F022..=0x1F029).contains(&(c as u32))
+    }
+
+    /// 花牌・季節牌の絵文字 (`i`番目、8枚ごとに繰り返す)
+    pub fn flower_glyph(i: usize) -> char {
+        std::char::from_u32(0x1F022 + (i % 8) as u32).unwrap()
+    }
+
+    /// ジョーカーの絵文字(🀪)
+    pub fn joker_glyph() -> char {
+        '\u{1F02A}'
+    }
+
+    /// 裏向きの牌の絵文字(🀫)
+    pub fn back_glyph() -> char {
+        '\u{1F02B}'
+    }
+
+    /// 読み飛ばす文字(空白と絵文字の異体字セレクタ)か否か
+    pub fn is_ignorable(c: char) -> bool {
+        c.is_whitespace() || c == '\u{FE0E}' || c == '\u{FE0F}'
+    }
+
+    /// MPSZ表記の字牌 (1z: 東, 2z: 南, 3z: 西, 4z: 北, 5z: 白, 6z: 發, 7z: 中)
+    pub fn from_honour_number(u: u8) -> Option<Tile> {
+        match u {
+            1 => Some(Wind::East.tile()),
+            2 => Some(Wind::South.tile()),
+            3 => Some(Wind::West.tile()),
+            4 => Some(Wind::North.tile()),
+            5 => Some(Dragon::White.tile()),
+            6 => Some(Dragon::Green.tile()),
+            7 => Some(Dragon::Red.tile()),
+            _ => None,
+        }
+    }
+
+    /// MPSZ表記の数字と種類 (`5m`なら`(5, 'm')`)
+    pub fn mpsz_parts(&self) -> (u8, char) {
+        match &self {
+            Tile::Character(u) => (*u, 'm'),
+            Tile::Circle(u) => (*u, 'p'),
+            Tile::Bamboo(u) => (*u, 's'),
+            Tile::Honour(Honour::Wind(wind)) => (match wind {
+                Wind::East => 1,
+                Wind::South => 2,
+                Wind::West => 3,
+                Wind::North => 4,
+            }, 'z'),
+            Tile::Honour(Honour::Dragon(dragon)) => (match dragon {
+                Dragon::White => 5,
+                Dragon::Green => 6,
+                Dragon::Red => 7,
+            }, 'z'),
+        }
+    }
+
+    /// MPSZ表記 (`5m`, `1z`など)
+    pub fn mpsz(&self) -> String {
+        let (u, suit) = self.mpsz_parts();
+        format!("{}{}", u, suit)
+    }
+
+    /// 全34種の牌 (萬子、筒子、索子、字牌の順)
+    pub fn all() -> Vec<Tile> {
+        let mut tiles = Vec::with_capacity(34);
+        (1..=9).for_each(|u| tiles.push(Tile::Character(u)));
+        (1..=9).for_each(|u| tiles.push(Tile::Circle(u)));
+        (1..=9).for_each(|u| tiles.push(Tile::Bamboo(u)));
+        (1..=7).for_each(|u| tiles.push(Tile::from_honour_number(u).unwrap()));
+        tiles
+    }
+
+    /// `Tile::all()`の中での位置 (0-33)
+    pub fn index(&self) -> usize {
+        match self.mpsz_parts() {
+            (u, 'm') => u as usize - 1,
+            (u, 'p') => u as usize + 8,
+            (u, 's') => u as usize + 17,
+            (u, _) => u as usize + 26,
+        }
+    }
+
+    /// この牌をドラ表示牌とした場合のドラ (9の次は1、北の次は東、中の次は白)
+    pub fn dora(&self) -> Tile {
+        match self {
+            Tile::Character(u) => Tile::Character(u % 9 + 1),
+            Tile::Circle(u) => Tile::Circle(u % 9 + 1),
+            Tile::Bamboo(u) => Tile::Bamboo(u % 9 + 1),
+            Tile::Honour(Honour::Wind(wind)) => match wind {
+                Wind::East => Wind::South,
+                Wind::South => Wind::West,
+                Wind::West => Wind::North,
+                Wind::North => Wind::East,
+            }.tile(),
+            Tile::Honour(Honour::Dragon(dragon)) => match dragon {
+                Dragon::White => Dragon::Green,
+                Dragon::Green => Dragon::Red,
+                Dragon::Red => Dragon::White,
+            }.tile(),
+        }
+    }
+
+    /// 三人麻雀でのドラ (萬子は1と9のみなので1の次は9、9の次は1)
+    pub fn sanma_dora(&self) -> Tile {
+        match self {
+            Tile::Character(1) => Tile::Character(9),
+            Tile::Character(9) => Tile::Character(1),
+            _ => self.dora(),
+        }
+    }
+
     pub fn next(&self) -> Option<Tile> {
         match self {
             Tile::Character(u) => {
@@ -0,0 +1,194 @@
+use crate::rng::Rng;
+use crate::tiles::Tile;
+use std::fmt::{Display, Formatter, Error};
+
+/// 王牌の枚数
+pub const DEAD_WALL: usize = 14;
+/// 嶺上牌の枚数 (槓は4回まで)
+pub const MAX_KONGS: usize = 4;
+/// ドラ表示牌の最大枚数 (ドラと槓ドラ4枚)
+pub const MAX_DORA_INDICATORS: usize = 5;
+/// 北抜きドラの最大枚数
+pub const MAX_NUKIDORA: usize = 4;
+
+/// 山の1枚 (赤五を区別する)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WallTile {
+    pub tile: Tile,
+    /// 赤五か
+    pub red: bool,
+}
+
+impl WallTile {
+    pub fn new(tile: Tile) -> Self {
+        WallTile { tile, red: false }
+    }
+
+    pub fn red(tile: Tile) -> Self {
+        WallTile { tile, red: true }
+    }
+}
+
+/// `{}`はMPSZ表記 (赤五は`0m`)、`{:#}`は牌の絵文字
+impl Display for WallTile {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if f.alternate() {
+            write!(f, "{:#}", self.tile)
+        } else if self.red {
+            write!(f, "0{}", self.tile.mpsz_parts().1)
+        } else {
+            write!(f, "{}", self.tile)
+        }
+    }
+}
+
+/// 山
+///
+/// 末尾の14枚を王牌とし、残りを前から順にツモる。王牌は先頭から嶺上牌4枚、
+/// ドラ表示牌5枚(ドラと槓ドラ)、裏ドラ表示牌5枚の順に並んでいるものとする。
+/// 嶺上牌を引くたびに海底が1枚手前にずれる。北抜きのある山では5枚目以降の嶺上牌を
+/// 海底側から補充する
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wall {
+    tiles: Vec<WallTile>,
+    /// 配牌に使ったシード
+    seed: Option<u64>,
+    /// ツモった枚数
+    drawn: usize,
+    /// 嶺上牌を引いた枚数
+    rinshan: usize,
+    /// めくったドラ表示牌の枚数
+    revealed: usize,
+    /// 嶺上牌を引ける枚数
+    replacements: usize,
+}
+
+impl Wall {
+    /// 136枚の牌 (`red_fives`が真の場合は各色の5を1枚ずつ赤五にする)
+    pub fn tile_set(red_fives: bool) -> Vec<WallTile> {
+        let mut tiles = Vec::with_capacity(136);
+        for tile in Tile::all() {
+            let red = red_fives && tile.is_suits() && tile.mpsz_parts().0 == 5;
+            tiles.push(if red { WallTile::red(tile.clone()) } else { WallTile::new(tile.clone()) });
+            (1..4).for_each(|_| tiles.push(WallTile::new(tile.clone())));
+        }
+        tiles
+    }
+
+    /// 三人麻雀の108枚 (2m-8mを除く、`red_fives`が真の場合は5pと5sを1枚ずつ赤五にする)
+    pub fn sanma_tile_set(red_fives: bool) -> Vec<WallTile> {
+        Wall::tile_set(red_fives).into_iter()
+            .filter(|tile| !matches!(tile.tile, Tile::Character(2..=8)))
+            .collect()
+    }
+
+    /// シードから山を積む
+    pub fn new(seed: u64, red_fives: bool) -> Self {
+        Wall::shuffled(Wall::tile_set(red_fives), seed)
+    }
+
+    /// シードから三人麻雀の山を積む (北抜きあり)
+    pub fn sanma(seed: u64, red_fives: bool) -> Self {
+        Wall::shuffled(Wall::sanma_tile_set(red_fives), seed).with_nukidora()
+    }
+
+    /// 北抜きの分も嶺上牌を引けるようにする
+    pub fn with_nukidora(self) -> Self {
+        Wall { replacements: MAX_KONGS + MAX_NUKIDORA, ..self }
+    }
+
+    /// 牌の組をシードで並べ替えて山を積む
+    pub fn shuffled(mut tiles: Vec<WallTile>, seed: u64) -> Self {
+        Rng::new(seed).shuffle(&mut tiles);
+        Wall { seed: Some(seed), ..Wall::from_order(tiles) }
+    }
+
+    /// 並び順をそのまま山にする (牌譜の再現など)
+    pub fn from_tiles(tiles: Vec<WallTile>) -> Result<Self, failure::Error> {
+        if tiles.len() <= DEAD_WALL + MAX_KONGS {
+            return Err(format_err!("山の枚数が足りません: {}枚", tiles.len()));
+        }
+        Ok(Wall::from_order(tiles))
+    }
+
+    fn from_order(tiles: Vec<WallTile>) -> Self {
+        Wall { tiles, seed: None, drawn: 0, rinshan: 0, revealed: 1, replacements: MAX_KONGS }
+    }
+
+    /// 積んだ順の全ての牌
+    pub fn tiles(&self) -> &Vec<WallTile> {
+        &self.tiles
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    fn dead_wall(&self) -> &[WallTile] {
+        &self.tiles[self.tiles.len() - DEAD_WALL..]
+    }
+
+    /// ツモれる残り枚数
+    pub fn remaining(&self) -> usize {
+        self.tiles.len() - DEAD_WALL - self.drawn - self.rinshan
+    }
+
+    /// 次のツモが海底牌か
+    pub fn is_last_tile(&self) -> bool {
+        self.remaining() == 1
+    }
+
+    /// 山が尽きたか (直前のツモが海底牌)
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// 山から1枚ツモる
+    pub fn draw(&mut self) -> Option<WallTile> {
+        if self.is_exhausted() {
+            return None;
+        }
+        self.drawn += 1;
+        Some(self.tiles[self.drawn - 1].clone())
+    }
+
+    /// 嶺上牌を1枚引く
+    ///
+    /// 4枚(北抜きのある山では8枚)引いた後や山が尽きた後は`None`を返す
+    pub fn draw_rinshan(&mut self) -> Option<WallTile> {
+        if self.rinshan >= self.replacements || self.is_exhausted() {
+            return None;
+        }
+        let tile = match self.rinshan.checked_sub(MAX_KONGS) {
+            Some(extra) => self.tiles[self.tiles.len() - DEAD_WALL - 1 - extra].clone(),
+            None => self.dead_wall()[self.rinshan].clone(),
+        };
+        self.rinshan += 1;
+        Some(tile)
+    }
+
+    /// 嶺上牌を引いた枚数
+    pub fn rinshan_drawn(&self) -> usize {
+        self.rinshan
+    }
+
+    /// 槓ドラをめくってその表示牌を返す
+    pub fn reveal_kan_dora(&mut self) -> Option<WallTile> {
+        if self.revealed >= MAX_DORA_INDICATORS {
+            return None;
+        }
+        self.revealed += 1;
+        Some(self.dead_wall()[MAX_KONGS + self.revealed - 1].clone())
+    }
+
+    /// めくったドラ表示牌
+    pub fn dora_indicators(&self) -> Vec<Tile> {
+        self.dead_wall()[MAX_KONGS..MAX_KONGS + self.revealed].iter().map(|tile| tile.tile.clone()).collect()
+    }
+
+    /// めくったドラ表示牌に対応する裏ドラ表示牌
+    pub fn ura_dora_indicators(&self) -> Vec<Tile> {
+        let start = MAX_KONGS + MAX_DORA_INDICATORS;
+        self.dead_wall()[start..start + self.revealed].iter().map(|tile| tile.tile.clone()).collect()
+    }
+}
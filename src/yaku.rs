@@ -1,5 +1,29 @@
 pub trait YakuAttributes {
     fn name(&self) -> String;
+
+    /// 識別子
+    fn id(&self) -> String {
+        id(&self.name())
+    }
+}
+
+/// 役の名前から識別子を作る (`"平和 / No-points hand"`なら`"no_points_hand"`)
+///
+/// 英語名の部分を小文字にし、英数字以外を`_`に置き換える
+pub fn id(name: &str) -> String {
+    let english = match name.rfind(" / ") {
+        Some(i) => &name[i + 3..],
+        None => name,
+    };
+    let mut id = String::new();
+    for c in english.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    id.trim_matches('_').to_string()
 }
 
 pub mod situation {
@@ -85,11 +109,18 @@ pub mod hand {
         pub sub: Option<Box<HandYaku>>,
         /// 府数(平和、七対子対応)
         pub fu: Option<Box<Fn(&bool) -> Fu>>,
+        /// 断么九か(喰いタンなしの場合に鳴いた手で数えない)
+        pub all_simple: bool,
     }
 
     impl HandYaku {
         pub fn new(name: &str, sub: Option<Box<HandYaku>>, rule: Box<Fn(&Wait) -> Option<Han>>, fu: Option<Box<Fn(&bool) -> Fu>>) -> Self {
-            HandYaku { name: name.to_string(), sub, rule, fu }
+            HandYaku { name: name.to_string(), sub, rule, fu, all_simple: false }
+        }
+
+        /// 断么九として扱う
+        pub fn as_all_simple(self) -> Self {
+            HandYaku { all_simple: true, ..self }
         }
     }
 